@@ -0,0 +1,200 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
+
+use crate::interpreter::errors::RuntimeError;
+use crate::interpreter::interpreter::{Map, Value, checked_index, kind_name, map_key, quoted, whole_number};
+use crate::interpreter::numbers::{as_number, int_value};
+use crate::utils::decimal::Decimal;
+use crate::utils::diagnostics::Span;
+
+/// A function written in Rust, available everywhere without being declared.
+/// Its arguments are checked against `arity` before `call` runs.
+#[derive(Debug)]
+pub(super) struct Builtin {
+    pub name: &'static str,
+    pub arity: usize,
+    pub call: fn(&[Value], Span) -> Result<Value, RuntimeError>,
+}
+
+pub(super) static BUILTINS: &[Builtin] = &[
+    Builtin { name: "len", arity: 1, call: len },
+    Builtin { name: "push", arity: 2, call: push },
+    Builtin { name: "pop", arity: 1, call: pop },
+    Builtin { name: "insert", arity: 3, call: insert },
+    Builtin { name: "remove", arity: 2, call: remove },
+    Builtin { name: "has", arity: 2, call: has },
+    Builtin { name: "keys", arity: 1, call: keys },
+    Builtin { name: "values", arity: 1, call: values },
+    Builtin { name: "str", arity: 1, call: str },
+    Builtin { name: "int", arity: 1, call: int },
+    Builtin { name: "float", arity: 1, call: float },
+    Builtin { name: "decimal", arity: 1, call: decimal },
+];
+
+/// Number of items in a list or a map, or of characters in a string.
+fn len(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::List(items) => Ok(Value::Int(items.borrow().len() as i64)),
+        Value::Map(map) => Ok(Value::Int(map.borrow().len() as i64)),
+        Value::String(s) => Ok(Value::Int(s.chars().count() as i64)),
+        other => Err(invalid_argument("len", "a list, a map or a string", other, span)),
+    }
+}
+
+/// Adds an item at the end of a list and gives back the new length.
+fn push(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    let mut items = expect_list("push", &args[0], span)?.borrow_mut();
+    items.push(args[1].clone());
+    Ok(Value::Int(items.len() as i64))
+}
+
+/// Takes the last item out of a list.
+fn pop(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    expect_list("pop", &args[0], span)?
+        .borrow_mut()
+        .pop()
+        .ok_or_else(|| RuntimeError::InvalidArgument {
+            function: "pop",
+            reason: "the list is empty".to_string(),
+            span,
+        })
+}
+
+/// Puts an item before `index` (or at the very end when `index` is the length) and gives back the new length.
+fn insert(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    let mut items = expect_list("insert", &args[0], span)?.borrow_mut();
+    let position = if whole_number(&args[1], span)? == items.len() as i64 {
+        items.len()
+    } else {
+        checked_index(&args[1], items.len(), span)?
+    };
+
+    items.insert(position, args[2].clone());
+    Ok(Value::Int(items.len() as i64))
+}
+
+/// Takes the item at `index` out of a list, or the entry for `key` out of a map, and gives back its value.
+fn remove(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::List(items) => {
+            let mut items = items.borrow_mut();
+            let position = checked_index(&args[1], items.len(), span)?;
+            Ok(items.remove(position))
+        }
+        Value::Map(map) => map.borrow_mut().remove(&args[1]).ok_or_else(|| RuntimeError::KeyNotFound {
+            key: quoted(&args[1]),
+            span,
+        }),
+        other => Err(invalid_argument("remove", "a list or a map", other, span)),
+    }
+}
+
+/// Whether a map has an entry for `key`.
+fn has(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    let key = map_key(args[1].clone(), span)?;
+    let found = expect_map("has", &args[0], span)?.borrow().get(&key).is_some();
+    Ok(Value::Bool(found as i8))
+}
+
+/// The keys of a map as a list, in the order they were added.
+fn keys(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    let keys = expect_map("keys", &args[0], span)?.borrow().keys();
+    Ok(Value::List(Rc::new(RefCell::new(keys))))
+}
+
+/// The values of a map as a list, in the same order as `keys`.
+fn values(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    let values = expect_map("values", &args[0], span)?.borrow().values();
+    Ok(Value::List(Rc::new(RefCell::new(values))))
+}
+
+/// The text `meow` shows for a value.
+fn str(args: &[Value], _span: Span) -> Result<Value, RuntimeError> {
+    Ok(Value::String(args[0].to_string()))
+}
+
+/// Turns a string (like `"-42"`), a float, a decimal or a bool into an int. Floats and decimals lose what comes after the dot.
+fn int(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    let no_int = |what: String| RuntimeError::InvalidArgument {
+        function: "int",
+        reason: format!("{} has no int value", what),
+        span,
+    };
+
+    match &args[0] {
+        Value::Int(_) | Value::BigInt(_) => Ok(args[0].clone()),
+        Value::Bool(b) => Ok(Value::Int(*b as i64)),
+        Value::Decimal(n) => Ok(int_value(n.trunc())),
+        Value::Float(n) => BigInt::from_f64(n.trunc()).map(int_value).ok_or_else(|| no_int(args[0].to_string())),
+        Value::String(s) => s.trim().parse::<BigInt>().map(int_value).map_err(|_| no_int(quoted(&args[0]))),
+        other => Err(invalid_argument("int", "a number, a string or a bool", other, span)),
+    }
+}
+
+/// Turns a string (like `"3.5"`), an int, a decimal or a bool into a float.
+fn float(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::String(s) => s.trim().parse::<f64>().map(Value::Float).map_err(|_| RuntimeError::InvalidArgument {
+            function: "float",
+            reason: format!("{} has no float value", quoted(&args[0])),
+            span,
+        }),
+        other => match as_number(other) {
+            Some(n) => Ok(Value::Float(n.to_float())),
+            None => Err(invalid_argument("float", "a number, a string or a bool", other, span)),
+        },
+    }
+}
+
+/// Turns a string (like `"0.1"`), an int, a float or a bool into an exact decimal. A float gives
+/// the decimal it is shown as, so `decimal(0.1)` is `0.1` rather than the binary value behind it.
+fn decimal(args: &[Value], span: Span) -> Result<Value, RuntimeError> {
+    let no_decimal = |what: String| RuntimeError::InvalidArgument {
+        function: "decimal",
+        reason: format!("{} has no decimal value", what),
+        span,
+    };
+
+    match &args[0] {
+        Value::String(s) => Decimal::parse(s.trim()).map(Value::Decimal).ok_or_else(|| no_decimal(quoted(&args[0]))),
+        Value::Float(n) if !n.is_finite() => Err(no_decimal(args[0].to_string())),
+        Value::Float(n) => Ok(Value::Decimal(Decimal::parse(&n.to_string()).expect("finite floats show as decimals"))),
+        other => match as_number(other).and_then(|n| n.to_decimal()) {
+            Some(n) => Ok(Value::Decimal(n)),
+            None => Err(invalid_argument("decimal", "a number, a string or a bool", other, span)),
+        },
+    }
+}
+
+fn expect_list<'v>(
+    function: &'static str,
+    value: &'v Value,
+    span: Span,
+) -> Result<&'v Rc<RefCell<Vec<Value>>>, RuntimeError> {
+    match value {
+        Value::List(items) => Ok(items),
+        other => Err(invalid_argument(function, "a list", other, span)),
+    }
+}
+
+fn expect_map<'v>(
+    function: &'static str,
+    value: &'v Value,
+    span: Span,
+) -> Result<&'v Rc<RefCell<Map>>, RuntimeError> {
+    match value {
+        Value::Map(map) => Ok(map),
+        other => Err(invalid_argument(function, "a map", other, span)),
+    }
+}
+
+fn invalid_argument(function: &'static str, expected: &str, found: &Value, span: Span) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        function,
+        reason: format!("expected {}, got a {}", expected, kind_name(found)),
+        span,
+    }
+}
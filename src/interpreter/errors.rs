@@ -0,0 +1,177 @@
+use std::fmt;
+
+use crate::utils::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub enum RuntimeError {
+    UndefinedVariable { name: String, span: Span },
+    UndefinedFunction { name: String, span: Span },
+    ArgumentCount { name: String, expected: usize, found: usize, span: Span },
+    UnsupportedOperands { operator: String, span: Span },
+    UnknownOperator { operator: String, span: Span },
+    CannotEvaluate { span: Span },
+    NotIterable { kind: &'static str, span: Span },
+    InvalidRange { reason: &'static str, span: Span },
+    NotCallable { kind: &'static str, span: Span },
+    UndeclaredAssignment { name: String, span: Span },
+    ConstantAssignment { name: String, span: Span },
+    ConstantRedeclaration { name: String, span: Span },
+    AlreadyDeclared { name: String, span: Span },
+    IndexOutOfBounds { index: i64, length: usize, span: Span },
+    InvalidIndex { span: Span },
+    NotIndexable { kind: &'static str, span: Span },
+    NotAssignable { kind: &'static str, span: Span },
+    InvalidArgument { function: &'static str, reason: String, span: Span },
+    KeyNotFound { key: String, span: Span },
+    InvalidKey { kind: &'static str, span: Span },
+    NoFields { kind: &'static str, span: Span },
+    UnknownField { type_name: String, field: String, span: Span },
+    NotAClass { kind: &'static str, span: Span },
+    UnknownVariant { enum_name: String, variant: String, span: Span },
+    NotAnEnum { kind: &'static str, span: Span },
+    PatternArity { variant: String, expected: usize, found: usize, span: Span },
+    NoMatch { value: String, span: Span },
+    ImmutableVariant { span: Span },
+    DivisionByZero { span: Span },
+    NegativeShift { span: Span },
+    NumberTooBig { span: Span },
+    RecursionTooDeep { limit: usize, span: Span },
+}
+
+impl RuntimeError {
+    pub fn span(&self) -> Span {
+        match self {
+            RuntimeError::UndefinedVariable { span, .. }
+            | RuntimeError::UndefinedFunction { span, .. }
+            | RuntimeError::ArgumentCount { span, .. }
+            | RuntimeError::UnsupportedOperands { span, .. }
+            | RuntimeError::UnknownOperator { span, .. }
+            | RuntimeError::CannotEvaluate { span }
+            | RuntimeError::NotIterable { span, .. }
+            | RuntimeError::InvalidRange { span, .. }
+            | RuntimeError::NotCallable { span, .. }
+            | RuntimeError::UndeclaredAssignment { span, .. }
+            | RuntimeError::ConstantAssignment { span, .. }
+            | RuntimeError::ConstantRedeclaration { span, .. }
+            | RuntimeError::AlreadyDeclared { span, .. }
+            | RuntimeError::IndexOutOfBounds { span, .. }
+            | RuntimeError::InvalidIndex { span }
+            | RuntimeError::NotIndexable { span, .. }
+            | RuntimeError::NotAssignable { span, .. }
+            | RuntimeError::InvalidArgument { span, .. }
+            | RuntimeError::KeyNotFound { span, .. }
+            | RuntimeError::InvalidKey { span, .. }
+            | RuntimeError::NoFields { span, .. }
+            | RuntimeError::UnknownField { span, .. }
+            | RuntimeError::NotAClass { span, .. }
+            | RuntimeError::UnknownVariant { span, .. }
+            | RuntimeError::NotAnEnum { span, .. }
+            | RuntimeError::PatternArity { span, .. }
+            | RuntimeError::NoMatch { span, .. }
+            | RuntimeError::ImmutableVariant { span }
+            | RuntimeError::DivisionByZero { span }
+            | RuntimeError::NegativeShift { span }
+            | RuntimeError::NumberTooBig { span }
+            | RuntimeError::RecursionTooDeep { span, .. } => *span,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuntimeError::UndefinedVariable { name, .. } => {
+                write!(f, "Variable \"{}\" is not defined", name)
+            }
+            RuntimeError::UndefinedFunction { name, .. } => {
+                write!(f, "Function \"{}\" is not defined", name)
+            }
+            RuntimeError::ArgumentCount { name, expected, found, .. } => write!(
+                f,
+                "\"{}\" expects {} arguments, but {} were provided",
+                name, expected, found
+            ),
+            RuntimeError::UnsupportedOperands { operator, .. } => {
+                write!(f, "Operator '{}' does not support those operands", operator)
+            }
+            RuntimeError::UnknownOperator { operator, .. } => {
+                write!(f, "Unknown operator: {}", operator)
+            }
+            RuntimeError::CannotEvaluate { .. } => write!(f, "This can't be used as a value"),
+            RuntimeError::NotIterable { kind, .. } => {
+                write!(f, "Can't loop over a value of kind {}", kind)
+            }
+            RuntimeError::InvalidRange { reason, .. } => write!(f, "Invalid range: {}", reason),
+            RuntimeError::NotCallable { kind, .. } => {
+                write!(f, "A value of kind {} can't be called", kind)
+            }
+            RuntimeError::UndeclaredAssignment { name, .. } => write!(
+                f,
+                "Can't assign to \"{}\", it was never declared (use nyan first)",
+                name
+            ),
+            RuntimeError::ConstantAssignment { name, .. } => {
+                write!(f, "Can't assign to \"{}\", it is a fowever constant", name)
+            }
+            RuntimeError::ConstantRedeclaration { name, .. } => {
+                write!(f, "\"{}\" is a fowever constant, it can't be declared again", name)
+            }
+            RuntimeError::AlreadyDeclared { name, .. } => write!(
+                f,
+                "\"{}\" is already declared in this scope, a fowever needs a name of its own",
+                name
+            ),
+            RuntimeError::IndexOutOfBounds { index, length, .. } => write!(
+                f,
+                "Index {} is out of bounds, there are only {} items",
+                index, length
+            ),
+            RuntimeError::InvalidIndex { .. } => write!(f, "Indices must be ints"),
+            RuntimeError::NotIndexable { kind, .. } => {
+                write!(f, "A value of kind {} can't be indexed", kind)
+            }
+            RuntimeError::NotAssignable { kind, .. } => {
+                write!(f, "Items of a {} can't be assigned to", kind)
+            }
+            RuntimeError::InvalidArgument { function, reason, .. } => {
+                write!(f, "Bad argument for {}: {}", function, reason)
+            }
+            RuntimeError::KeyNotFound { key, .. } => write!(f, "Key {} is not in the map", key),
+            RuntimeError::InvalidKey { kind, .. } => write!(
+                f,
+                "Map keys must be strings, numbers or bools, not a {}",
+                kind
+            ),
+            RuntimeError::NoFields { kind, .. } => {
+                write!(f, "A value of kind {} has no fields", kind)
+            }
+            RuntimeError::UnknownField { type_name, field, .. } => {
+                write!(f, "{} has no field \"{}\"", type_name, field)
+            }
+            RuntimeError::NotAClass { kind, .. } => {
+                write!(f, "A kwass can only inherit from another kwass, not from a {}", kind)
+            }
+            RuntimeError::UnknownVariant { enum_name, variant, .. } => {
+                write!(f, "{} has no variant \"{}\"", enum_name, variant)
+            }
+            RuntimeError::NotAnEnum { kind, .. } => {
+                write!(f, "A value of kind {} is not a flavor, it has no variants to match", kind)
+            }
+            RuntimeError::PatternArity { variant, expected, found, .. } => write!(
+                f,
+                "{} carries {} values, but the pattern has {}",
+                variant, expected, found
+            ),
+            RuntimeError::NoMatch { value, .. } => write!(f, "No meowtch arm matches {}", value),
+            RuntimeError::ImmutableVariant { .. } => {
+                write!(f, "The fields of a variant can't be changed, build a new one instead")
+            }
+            RuntimeError::DivisionByZero { .. } => write!(f, "Can't divide an int or a decimal by 0"),
+            RuntimeError::NegativeShift { .. } => write!(f, "Can't shift by a negative number of bits"),
+            RuntimeError::NumberTooBig { .. } => write!(f, "That number would be too big to work out"),
+            RuntimeError::RecursionTooDeep { limit, .. } => {
+                write!(f, "Too much recursion, more than {} sparkle calls inside each other", limit)
+            }
+        }
+    }
+}
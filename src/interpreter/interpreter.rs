@@ -1,58 +1,497 @@
+use crate::interpreter::builtins::{BUILTINS, Builtin};
+use crate::interpreter::errors::RuntimeError;
+use crate::interpreter::numbers::{
+    Number, arithmetic, as_number, bitwise, bitwise_not, compare_numbers, int_value, negate, power, unary_plus,
+};
 use crate::parser::ast::*;
+use crate::utils::decimal::Decimal;
+use crate::utils::diagnostics::Span;
 use crate::utils::owo::owoify;
+use num_bigint::{BigInt, Sign};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
 
 // === Runtime Structures ===
 
+/// One scope: the global one, the locals of a function call, one pass of a `fow` loop
+/// (its loop variable and what its body declares), or a `meowtch` arm (the names its pattern
+/// bound). Other blocks (`nani`, `whiwe`) share the scope they are in.
+///
+/// Names are looked up from the innermost scope outwards, so a `nyan` inside a
+/// function shadows a global with the same name without changing it.
 #[derive(Debug, Default)]
 struct Environment {
-    functions: HashMap<String, FunctionDeclarationNode>,
     variables: HashMap<String, Value>,
+    /// Names declared with `fowever` in this scope, with the span of their declaration.
+    constants: HashMap<String, Span>,
+    parent: Option<Env>,
 }
 
+/// Result of assigning to an existing name.
+enum Assignment {
+    Done,
+    Undeclared,
+    Constant,
+}
+
+type Env = Rc<RefCell<Environment>>;
+
+impl Environment {
+    /// The scope of the top-level code. Builtins live in a scope of their own around it,
+    /// so a script can reuse their names without clashing.
+    fn new_global() -> Env {
+        let mut builtins = Environment::default();
+        for builtin in BUILTINS {
+            builtins.variables.insert(builtin.name.to_string(), Value::Builtin(builtin));
+        }
+        Environment::new_child(&Rc::new(RefCell::new(builtins)))
+    }
+
+    fn new_child(parent: &Env) -> Env {
+        Rc::new(RefCell::new(Environment {
+            parent: Some(Rc::clone(parent)),
+            ..Environment::default()
+        }))
+    }
+
+    /// Declares `name` in this scope, `constant` being the span of a `fowever`.
+    /// Returns `false` when a constant is in the way: it can't be declared over, and it can't
+    /// take a name already used here. The same `fowever` running again (in a loop) is fine.
+    fn declare_variable(&mut self, name: &str, value: Value, constant: Option<Span>) -> bool {
+        let allowed = match (self.constants.get(name), constant) {
+            (Some(existing), Some(span)) => *existing == span,
+            (Some(_), None) => false,
+            (None, Some(_)) => !self.variables.contains_key(name),
+            (None, None) => true,
+        };
+
+        if allowed {
+            self.variables.insert(name.to_string(), value);
+            if let Some(span) = constant {
+                self.constants.insert(name.to_string(), span);
+            }
+        }
+        allowed
+    }
+
+    fn is_constant(&self, name: &str) -> bool {
+        self.constants.contains_key(name)
+    }
+
+    /// Updates the closest scope where `name` exists, unless it is a constant there.
+    fn assign_variable(&mut self, name: &str, value: Value) -> Assignment {
+        if let Some(slot) = self.variables.get_mut(name) {
+            if self.constants.contains_key(name) {
+                return Assignment::Constant;
+            }
+            *slot = value;
+            return Assignment::Done;
+        }
+        match &self.parent {
+            Some(parent) => parent.borrow_mut().assign_variable(name, value),
+            None => Assignment::Undeclared,
+        }
+    }
+
+    fn get_variable(&self, name: &str) -> Option<Value> {
+        match self.variables.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref()?.borrow().get_variable(name),
+        }
+    }
+}
+
+/// Lists, maps, records and instances are shared: copying one into another variable doesn't
+/// copy its items, so `push` through either name changes both. Variants can't be changed once built.
 #[derive(Debug, Clone)]
-enum Value {
-    Number(f64),
+pub(super) enum Value {
+    Int(i64),
+    /// An int too big for `Int`, arithmetic switches between the two as needed.
+    BigInt(BigInt),
+    Float(f64),
+    /// An exact decimal, from a `0.1d` literal or `decimal()`.
+    Decimal(Decimal),
     String(String),
     Bool(i8),
+    /// `nuww`, also what a function gives back when it doesn't `bringback` a value.
+    Nil,
+    Function(Rc<Function>),
+    Builtin(&'static Builtin),
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<Map>>),
+    RecordType(Rc<RecordType>),
+    Record(Rc<RefCell<Record>>),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    Enum(Rc<EnumType>),
+    /// `Enum.Variant` for a variant that carries values, calling it builds the variant.
+    VariantConstructor(Rc<EnumType>, usize),
+    Variant(Rc<Variant>),
 }
 
-// === Entry Point ===
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::BigInt(n) => write!(f, "{}", n),
+            // Whole floats keep their dot, so `3.0` and `3` don't look the same
+            Value::Float(n) if n.fract() == 0.0 => write!(f, "{:.1}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Decimal(n) => write!(f, "{}", n),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", if *b == 1 { "twue" } else { "fawse" }),
+            Value::Nil => write!(f, "nuww"),
+            Value::Function(func) => write!(f, "<sparkle {}>", func.name),
+            Value::Builtin(builtin) => write!(f, "<sparkle {}>", builtin.name),
+            Value::RecordType(record_type) => write!(f, "<bento {}>", record_type.name),
+            Value::Class(class) => write!(f, "<kwass {}>", class.name),
+            Value::Enum(enum_type) => write!(f, "<flavor {}>", enum_type.name),
+            Value::VariantConstructor(enum_type, index) => {
+                write!(f, "<sparkle {}.{}>", enum_type.name, enum_type.variants[*index].name)
+            }
+            Value::List(_) | Value::Map(_) | Value::Record(_) | Value::Instance(_) | Value::Variant(_) => {
+                write_item(f, self, &mut Vec::new())
+            }
+        }
+    }
+}
 
-pub fn run(ast: &[ASTNode]) {
-    let mut env = Environment::default();
+/// Writes a value found inside a list, a map, a record or an instance: strings get their quotes back,
+/// and a value that contains itself is shown as `[...]`, `{...}` or `Name(...)` instead of going on forever.
+fn write_item(f: &mut fmt::Formatter, value: &Value, open: &mut Vec<*const ()>) -> fmt::Result {
+    match value {
+        Value::List(items) => {
+            let pointer = Rc::as_ptr(items) as *const ();
+            if open.contains(&pointer) {
+                return write!(f, "[...]");
+            }
+            open.push(pointer);
 
-    // Pass 1: Collect function declarations
-    for node in ast {
-        if let ASTNode::FunctionDeclaration(func) = node {
-            env.functions.insert(func.name.clone(), func.clone());
+            write!(f, "[")?;
+            for (i, item) in items.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_item(f, item, open)?;
+            }
+            open.pop();
+            write!(f, "]")
         }
+        Value::Map(map) => {
+            let pointer = Rc::as_ptr(map) as *const ();
+            if open.contains(&pointer) {
+                return write!(f, "{{...}}");
+            }
+            open.push(pointer);
+
+            write!(f, "{{")?;
+            for (i, (key, value)) in map.borrow().entries.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}: ", quoted(key))?;
+                write_item(f, value, open)?;
+            }
+            open.pop();
+            write!(f, "}}")
+        }
+        Value::Record(record) => {
+            let record = record.borrow();
+            let pointer = &*record as *const Record as *const ();
+            let fields = record.record_type.fields.iter().zip(&record.values);
+            write_fields(f, &record.record_type.name, pointer, fields, open)
+        }
+        Value::Instance(instance) => {
+            let instance = instance.borrow();
+            let pointer = &*instance as *const Instance as *const ();
+            let fields = instance.fields.iter().map(|(name, value)| (name, value));
+            write_fields(f, &instance.class.name, pointer, fields, open)
+        }
+        Value::Variant(variant) => {
+            let declared = variant.declared();
+            let name = format!("{}.{}", variant.enum_type.name, declared.name);
+            if declared.fields.is_empty() {
+                return write!(f, "{}", name);
+            }
+            let pointer = Rc::as_ptr(variant) as *const ();
+            write_fields(f, &name, pointer, declared.fields.iter().zip(&variant.values), open)
+        }
+        other => write!(f, "{}", quoted(other)),
     }
+}
 
-    // Pass 2: Execute statements
-    for node in ast {
-        match node {
-            ASTNode::OwO(_) | ASTNode::Print(_) | ASTNode::FunctionCall(_) | ASTNode::VariableDeclaration(_) | ASTNode::KindOf(_) => {
-                execute(node, &mut env);
+/// Writes a record, an instance or a variant as `Name(field: value, ...)`.
+fn write_fields<'v>(
+    f: &mut fmt::Formatter,
+    name: &str,
+    pointer: *const (),
+    fields: impl Iterator<Item = (&'v String, &'v Value)>,
+    open: &mut Vec<*const ()>,
+) -> fmt::Result {
+    if open.contains(&pointer) {
+        return write!(f, "{}(...)", name);
+    }
+    open.push(pointer);
+
+    write!(f, "{}(", name)?;
+    for (i, (field, value)) in fields.enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}: ", field)?;
+        write_item(f, value, open)?;
+    }
+    open.pop();
+    write!(f, ")")
+}
+
+/// Shows strings with their quotes, to tell `"1"` and `1` apart.
+pub(super) fn quoted(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("\"{}\"", s),
+        other => other.to_string(),
+    }
+}
+
+/// Entries of a map, kept in the order their keys were first added.
+/// Keys are strings, numbers or bools; looking one up compares it with every key.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Map {
+    entries: Vec<(Value, Value)>,
+}
+
+impl Map {
+    fn position(&self, key: &Value) -> Option<usize> {
+        self.entries.iter().position(|(k, _)| values_equal(k, key))
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(super) fn get(&self, key: &Value) -> Option<&Value> {
+        self.position(key).map(|i| &self.entries[i].1)
+    }
+
+    /// Adds an entry, or replaces the value of an existing key without moving it.
+    fn insert(&mut self, key: Value, value: Value) {
+        match self.position(&key) {
+            Some(i) => self.entries[i].1 = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+
+    pub(super) fn remove(&mut self, key: &Value) -> Option<Value> {
+        let i = self.position(key)?;
+        Some(self.entries.remove(i).1)
+    }
+
+    pub(super) fn keys(&self) -> Vec<Value> {
+        self.entries.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    pub(super) fn values(&self) -> Vec<Value> {
+        self.entries.iter().map(|(_, v)| v.clone()).collect()
+    }
+}
+
+/// A `sparkle`, named or anonymous, along with the scope it was created in.
+/// Calls run in a child of that scope, which is what lets closures see their surroundings.
+pub(super) struct Function {
+    name: String,
+    params: Vec<String>,
+    body: Rc<[ASTNode]>,
+    closure: Env,
+}
+
+impl Function {
+    fn create(name: &str, params: &[ASTNode], body: &Rc<[ASTNode]>, closure: &Env) -> Rc<Function> {
+        let params = params
+            .iter()
+            .filter_map(|param| match param {
+                ASTNode::VariableDeclaration(decl) => Some(decl.name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Rc::new(Function {
+            name: name.to_string(),
+            params,
+            body: Rc::clone(body),
+            closure: Rc::clone(closure),
+        })
+    }
+
+    fn create_value(name: &str, params: &[ASTNode], body: &Rc<[ASTNode]>, closure: &Env) -> Value {
+        Value::Function(Function::create(name, params, body, closure))
+    }
+
+    /// Makes a copy of this method for `instance`: it runs in a scope where `sewf` is the instance,
+    /// and `supew` is the parent of `class`, the class the method was found in.
+    fn bind(&self, instance: &Rc<RefCell<Instance>>, class: &Class) -> Rc<Function> {
+        let scope = Environment::new_child(&self.closure);
+        {
+            let mut scope = scope.borrow_mut();
+            scope.variables.insert("sewf".to_string(), Value::Instance(Rc::clone(instance)));
+            if let Some(parent) = &class.superclass {
+                scope.variables.insert("supew".to_string(), Value::Class(Rc::clone(parent)));
             }
-            _ => {}
         }
+
+        Rc::new(Function {
+            name: self.name.clone(),
+            params: self.params.clone(),
+            body: Rc::clone(&self.body),
+            closure: scope,
+        })
+    }
+}
+
+/// A `bento` type. Calling it builds a record with one value per field, in order.
+#[derive(Debug)]
+pub(super) struct RecordType {
+    name: String,
+    fields: Vec<String>,
+}
+
+/// A value built from a `bento`, its fields can be read and changed with `record.field`.
+#[derive(Debug)]
+pub(super) struct Record {
+    record_type: Rc<RecordType>,
+    values: Vec<Value>,
+}
+
+impl Record {
+    fn field_index(&self, field: &str) -> Option<usize> {
+        self.record_type.fields.iter().position(|f| f == field)
+    }
+}
+
+/// A `kwass`. Calling it makes an instance and runs its `init` method on it, if there is one.
+#[derive(Debug)]
+pub(super) struct Class {
+    name: String,
+    superclass: Option<Rc<Class>>,
+    methods: HashMap<String, Rc<Function>>,
+}
+
+impl Class {
+    /// Finds a method in this class or its parents, along with the class it was found in.
+    fn find_method<'c>(self: &'c Rc<Class>, name: &str) -> Option<(&'c Rc<Function>, &'c Rc<Class>)> {
+        match self.methods.get(name) {
+            Some(method) => Some((method, self)),
+            None => self.superclass.as_ref()?.find_method(name),
+        }
+    }
+
+    /// How many arguments calling the class takes, the ones of `init`.
+    fn arity(self: &Rc<Class>) -> usize {
+        self.find_method("init").map_or(0, |(init, _)| init.params.len())
+    }
+}
+
+/// A value made by calling a `kwass`. Unlike records, fields can be added at any time with `sewf.name = value`.
+#[derive(Debug)]
+pub(super) struct Instance {
+    class: Rc<Class>,
+    fields: Vec<(String, Value)>,
+}
+
+impl Instance {
+    fn field(&self, name: &str) -> Option<&Value> {
+        self.fields.iter().find(|(field, _)| field == name).map(|(_, value)| value)
+    }
+
+    fn set_field(&mut self, name: &str, value: Value) {
+        match self.fields.iter_mut().find(|(field, _)| field == name) {
+            Some((_, slot)) => *slot = value,
+            None => self.fields.push((name.to_string(), value)),
+        }
+    }
+}
+
+/// A `flavor`: a type whose values are one of its variants, each carrying its own fields.
+#[derive(Debug)]
+pub(super) struct EnumType {
+    name: String,
+    variants: Vec<EnumVariant>,
+}
+
+impl EnumType {
+    fn variant_index(&self, name: &str) -> Option<usize> {
+        self.variants.iter().position(|v| v.name == name)
     }
 }
 
+/// A value of a `flavor`, like `Shape.Circle(2)` or `Shape.Empty`.
+#[derive(Debug)]
+pub(super) struct Variant {
+    enum_type: Rc<EnumType>,
+    index: usize,
+    values: Vec<Value>,
+}
+
+impl Variant {
+    fn declared(&self) -> &EnumVariant {
+        &self.enum_type.variants[self.index]
+    }
+}
+
+// Written by hand: deriving it would print the captured scope, which can contain the function itself
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Function({})", self.name)
+    }
+}
+
+/// What a statement asks its enclosing block to do next.
+#[derive(Debug)]
+enum ControlFlow {
+    Normal,
+    Return(Value),
+    Break,
+    Continue,
+}
+
+// === Entry Point ===
+
+pub fn run(ast: &[ASTNode]) -> Result<(), RuntimeError> {
+    let env = Environment::new_global();
+    execute_block(ast, &env)?;
+    Ok(())
+}
+
 // === Evaluation ===
 
-fn evaluate(node: &ASTNode, env: &Environment) -> Value {
+fn evaluate(node: &ASTNode, env: &Env) -> Result<Value, RuntimeError> {
     match node {
-        ASTNode::StringLiteral(s) => Value::String(s.value.clone()),
-        ASTNode::NumberLiteral(n) => Value::Number(n.value),
-        ASTNode::BoolLiteral(b) => Value::Bool(b.value),
+        ASTNode::StringLiteral(s) => Ok(Value::String(s.value.clone())),
+        // Every part is shown like `meow` would, so any value can go in a string
+        ASTNode::Interpolation(interpolation) => {
+            let mut text = String::new();
+            for part in &interpolation.parts {
+                text.push_str(&evaluate(part, env)?.to_string());
+            }
+            Ok(Value::String(text))
+        }
+        ASTNode::NumberLiteral(n) => Ok(match &n.value {
+            Numeric::Int(value) => Value::Int(*value),
+            Numeric::BigInt(value) => Value::BigInt(value.clone()),
+            Numeric::Float(value) => Value::Float(*value),
+            Numeric::Decimal(value) => Value::Decimal(value.clone()),
+        }),
+        ASTNode::BoolLiteral(b) => Ok(Value::Bool(b.value)),
+        ASTNode::NilLiteral(_) => Ok(Value::Nil),
 
         ASTNode::VariableReference(var) => env
-            .variables
-            .get(&var.name)
-            .cloned()
-            .unwrap_or_else(|| panic!("Variable \"{}\" is not defined", var.name)),
+            .borrow()
+            .get_variable(&var.name)
+            .ok_or_else(|| RuntimeError::UndefinedVariable {
+                name: var.name.clone(),
+                span: var.span,
+            }),
 
         ASTNode::Print(p) => evaluate(&p.expression, env),
         ASTNode::OwO(p) => evaluate(&p.expression, env),
@@ -60,152 +499,1031 @@ fn evaluate(node: &ASTNode, env: &Environment) -> Value {
         ASTNode::KindOf(k) => evaluate_kind_of(k, env),
 
         ASTNode::BinaryExpression(expr) => {
-            let left = evaluate(&expr.left, env);
-            let right = evaluate(&expr.right, env);
+            let left = evaluate(&expr.left, env)?;
+            let right = evaluate(&expr.right, env)?;
+            apply_binary_operator(&expr.operator, &left, &right, expr.span)
+        }
 
+        ASTNode::LogicalExpression(expr) => {
+            // The right side only runs when the left one doesn't decide the result
+            let left = is_truthy(&evaluate(&expr.left, env)?);
+            let result = match expr.operator.as_str() {
+                "&&" => left && is_truthy(&evaluate(&expr.right, env)?),
+                "||" => left || is_truthy(&evaluate(&expr.right, env)?),
+                op => {
+                    return Err(RuntimeError::UnknownOperator {
+                        operator: op.to_string(),
+                        span: expr.span,
+                    });
+                }
+            };
+            Ok(bool_value(result))
+        }
+
+        ASTNode::UnaryExpression(expr) => {
+            let operand = evaluate(&expr.operand, env)?;
             match expr.operator.as_str() {
-                "+" => match (&left, &right) {
-                    (Value::Number(l), Value::Number(r)) => Value::Number(l + r),
-                    (Value::String(l), Value::String(r)) => Value::String(l.clone() + r),
-                    (Value::String(l), Value::Number(r)) => {
-                        Value::String(l.clone() + &r.to_string())
-                    }
-                    (Value::Number(l), Value::String(r)) => Value::String(l.to_string() + r),
-                    (Value::Bool(l), Value::Bool(r)) => Value::Number((l+r).into()),
-                    (Value::Bool(l), Value::Number(r)) => Value::Number(*l as f64 + r),
-                    (Value::Number(l), Value::Bool(r)) => Value::Number(l + *r as f64),
-                    _ => panic!("Operator '+' does not support those operands"),
-
-                },
-                "-" => match (&left, &right) {
-                    (Value::Number(l), Value::Number(r)) => Value::Number(l - r),
-                    (Value::Bool(l), Value::Bool(r)) => Value::Number((l-r).into()),
-                    (Value::Bool(l), Value::Number(r)) => Value::Number(*l as f64 - r),
-                    (Value::Number(l), Value::Bool(r)) => Value::Number(l - *r as f64),
-                    _ => panic!("Operator '-' does not support those operands"),
-                },
-                "*" => match (&left, &right) {
-                    (Value::Number(l), Value::Number(r)) => Value::Number(l * r),
-                    (Value::Bool(l), Value::Bool(r)) => Value::Number((l*r).into()),
-                    (Value::Bool(l), Value::Number(r)) => Value::Number(*l as f64 * r),
-                    (Value::Number(l), Value::Bool(r)) => Value::Number(l * *r as f64),
-                    _ => panic!("Operator '*' does not support those operands"),
-                },
-                "/" => match (&left, &right) {
-                    (Value::Number(l), Value::Number(r)) => Value::Number(l / r),
-                    (Value::Bool(l), Value::Bool(r)) => Value::Number((l/r).into()),
-                    (Value::Bool(l), Value::Number(r)) => Value::Number(*l as f64 / r),
-                    (Value::Number(l), Value::Bool(r)) => Value::Number(l / *r as f64),
-                    _ => panic!("Operator '/' does not support those operands"),
-                },
-                op => panic!("Unknown operator: {}", op),
+                "!" => Ok(bool_value(!is_truthy(&operand))),
+                "-" | "+" | "~" => {
+                    let result = match expr.operator.as_str() {
+                        "-" => negate(&operand),
+                        "+" => unary_plus(&operand),
+                        _ => bitwise_not(&operand),
+                    };
+                    result.ok_or_else(|| RuntimeError::UnsupportedOperands {
+                        operator: expr.operator.clone(),
+                        span: expr.span,
+                    })
+                }
+                op => Err(RuntimeError::UnknownOperator {
+                    operator: op.to_string(),
+                    span: expr.span,
+                }),
             }
         }
 
         ASTNode::FunctionCall(call) => evaluate_function_call(call, env),
 
-        _ => panic!("Cannot evaluate node of type: {:?}", node),
+        ASTNode::Lambda(lambda) => Ok(Function::create_value("anonymous", &lambda.params, &lambda.body, env)),
+
+        ASTNode::List(list) => {
+            let mut items = Vec::new();
+            for element in &list.elements {
+                items.push(evaluate(element, env)?);
+            }
+            Ok(Value::List(Rc::new(RefCell::new(items))))
+        }
+
+        ASTNode::Map(map_node) => {
+            let mut map = Map::default();
+            for (key, value) in &map_node.entries {
+                let key = map_key(evaluate(key, env)?, key.span())?;
+                map.insert(key, evaluate(value, env)?);
+            }
+            Ok(Value::Map(Rc::new(RefCell::new(map))))
+        }
+
+        ASTNode::Index(index) => {
+            let target = evaluate(&index.target, env)?;
+            let position = evaluate(&index.index, env)?;
+            match &target {
+                Value::Map(map) => map.borrow().get(&position).cloned().ok_or_else(|| {
+                    RuntimeError::KeyNotFound {
+                        key: quoted(&position),
+                        span: index.span,
+                    }
+                }),
+                Value::List(items) => {
+                    let items = items.borrow();
+                    Ok(items[checked_index(&position, items.len(), index.span)?].clone())
+                }
+                Value::String(s) => {
+                    let chars: Vec<char> = s.chars().collect();
+                    let i = checked_index(&position, chars.len(), index.span)?;
+                    Ok(Value::String(chars[i].to_string()))
+                }
+                other => Err(RuntimeError::NotIndexable {
+                    kind: kind_name(other),
+                    span: index.target.span(),
+                }),
+            }
+        }
+
+        ASTNode::Slice(slice) => evaluate_slice(slice, env),
+
+        ASTNode::MemberAccess(access) => evaluate_member_access(access, env),
+
+        ASTNode::Match(match_node) => {
+            let subject = evaluate(&match_node.subject, env)?;
+            let value = run_match(match_node, &subject, env, |body, arm_env| match body {
+                MatchBody::Expression(expr) => evaluate(expr, arm_env),
+                MatchBody::Block(_) => Err(RuntimeError::CannotEvaluate { span: match_node.span }),
+            })?;
+            value.ok_or_else(|| RuntimeError::NoMatch {
+                value: quoted(&subject),
+                span: match_node.span,
+            })
+        }
+
+        ASTNode::Super(super_node) => {
+            let lookup = |name: &str| env.borrow().get_variable(name);
+            let (Some(Value::Class(parent)), Some(Value::Instance(instance))) = (lookup("supew"), lookup("sewf")) else {
+                return Err(RuntimeError::CannotEvaluate { span: super_node.span });
+            };
+
+            match parent.find_method(&super_node.method) {
+                Some((method, class)) => Ok(Value::Function(method.bind(&instance, class))),
+                None => Err(RuntimeError::UnknownField {
+                    type_name: parent.name.clone(),
+                    field: super_node.method.clone(),
+                    span: super_node.span,
+                }),
+            }
+        }
+
+        _ => Err(RuntimeError::CannotEvaluate { span: node.span() }),
     }
 }
 
-fn evaluate_function_call(call: &FunctionCallNode, env: &Environment) -> Value {
-    let func = env
-        .functions
-        .get(&call.name)
-        .unwrap_or_else(|| panic!("Function \"{}\" is not defined", call.name));
+/// Shared by binary expressions and compound assignments like `+=`.
+fn apply_binary_operator(operator: &str, left: &Value, right: &Value, span: Span) -> Result<Value, RuntimeError> {
+    let unsupported = || RuntimeError::UnsupportedOperands {
+        operator: operator.to_string(),
+        span,
+    };
 
-    let mut local_env = Environment::default();
+    match operator {
+        "+" => match (left, right) {
+            (Value::String(l), Value::String(r)) => Ok(Value::String(l.clone() + r)),
+            (Value::String(l), Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Decimal(_)) => {
+                Ok(Value::String(l.clone() + &right.to_string()))
+            }
+            (Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Decimal(_), Value::String(r)) => {
+                Ok(Value::String(left.to_string() + r))
+            }
+            (Value::List(l), Value::List(r)) => {
+                let items = l.borrow().iter().chain(r.borrow().iter()).cloned().collect();
+                Ok(Value::List(Rc::new(RefCell::new(items))))
+            }
+            _ => arithmetic(operator, left, right, span)?.ok_or_else(unsupported),
+        },
+        "-" | "*" | "/" | "//" | "%" => arithmetic(operator, left, right, span)?.ok_or_else(unsupported),
+        "**" => power(left, right, span)?.ok_or_else(unsupported),
+        "&" | "|" | "^" | "<<" | ">>" => bitwise(operator, left, right, span)?.ok_or_else(unsupported),
+        "==" => Ok(bool_value(values_equal(left, right))),
+        "!=" => Ok(bool_value(!values_equal(left, right))),
+        "<" | "<=" | ">" | ">=" => {
+            let ordering = compare_values(left, right).ok_or_else(unsupported)?;
+            let result = match operator {
+                "<" => ordering.is_lt(),
+                "<=" => ordering.is_le(),
+                ">" => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            };
+            Ok(bool_value(result))
+        }
+        op => Err(RuntimeError::UnknownOperator {
+            operator: op.to_string(),
+            span,
+        }),
+    }
+}
 
-    if call.arguments.len() != func.params.len() {
-        panic!(
-            "Function \"{}\" expects {} arguments, but {} were provided",
-            func.name,
-            func.params.len(),
-            call.arguments.len()
-        );
+fn evaluate_function_call(call: &FunctionCallNode, env: &Env) -> Result<Value, RuntimeError> {
+    let callee = match (evaluate(&call.callee, env), call.callee.as_ref()) {
+        (Err(RuntimeError::UndefinedVariable { .. }), ASTNode::VariableReference(var)) => {
+            return Err(RuntimeError::UndefinedFunction {
+                name: var.name.clone(),
+                span: call.span,
+            });
+        }
+        (result, _) => result?,
+    };
+
+    let (name, arity) = match &callee {
+        Value::Function(func) => (func.name.as_str(), func.params.len()),
+        Value::Builtin(builtin) => (builtin.name, builtin.arity),
+        Value::RecordType(record_type) => (record_type.name.as_str(), record_type.fields.len()),
+        Value::Class(class) => (class.name.as_str(), class.arity()),
+        Value::VariantConstructor(enum_type, index) => {
+            let variant = &enum_type.variants[*index];
+            (variant.name.as_str(), variant.fields.len())
+        }
+        other => {
+            return Err(RuntimeError::NotCallable {
+                kind: kind_name(other),
+                span: call.callee.span(),
+            });
+        }
+    };
+
+    if call.arguments.len() != arity {
+        return Err(RuntimeError::ArgumentCount {
+            name: name.to_string(),
+            expected: arity,
+            found: call.arguments.len(),
+            span: call.span,
+        });
     }
 
-    for (param, arg) in func.params.iter().zip(&call.arguments) {
-        let value = evaluate(arg, env);
-        if let ASTNode::VariableDeclaration(var_decl) = param {
-            local_env.variables.insert(var_decl.name.clone(), value);
+    let mut arguments = Vec::new();
+    for arg in &call.arguments {
+        arguments.push(evaluate(arg, env)?);
+    }
+
+    match callee {
+        Value::Builtin(builtin) => (builtin.call)(&arguments, call.span),
+        Value::Function(func) => call_function(&func, arguments, call.span),
+        Value::RecordType(record_type) => Ok(Value::Record(Rc::new(RefCell::new(Record {
+            record_type,
+            values: arguments,
+        })))),
+        Value::Class(class) => {
+            let instance = Rc::new(RefCell::new(Instance {
+                class: Rc::clone(&class),
+                fields: Vec::new(),
+            }));
+            if let Some((init, owner)) = class.find_method("init") {
+                call_function(&init.bind(&instance, owner), arguments, call.span)?;
+            }
+            Ok(Value::Instance(instance))
         }
+        Value::VariantConstructor(enum_type, index) => Ok(Value::Variant(Rc::new(Variant {
+            enum_type,
+            index,
+            values: arguments,
+        }))),
+        _ => unreachable!("only functions get past the check above"),
     }
+}
+
+/// How many `sparkle` calls can be running inside each other before the script is stopped,
+/// well before the interpreter itself would run out of stack.
+const MAX_CALL_DEPTH: usize = 1000;
 
-    let mut return_value = None;
-    for stmt in &func.body {
-        return_value = execute(stmt, &mut local_env);
-        if return_value.is_some() {
-            break;
+thread_local! {
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Runs `func`, `span` being the call it comes from.
+fn call_function(func: &Function, arguments: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
+    let depth = CALL_DEPTH.get();
+    if depth >= MAX_CALL_DEPTH {
+        return Err(RuntimeError::RecursionTooDeep {
+            limit: MAX_CALL_DEPTH,
+            span,
+        });
+    }
+
+    CALL_DEPTH.set(depth + 1);
+    let result = run_function_body(func, arguments);
+    CALL_DEPTH.set(depth);
+    result
+}
+
+fn run_function_body(func: &Function, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let local_env = Environment::new_child(&func.closure);
+    for (param, value) in func.params.iter().zip(arguments) {
+        local_env.borrow_mut().variables.insert(param.clone(), value);
+    }
+
+    match execute_block(&func.body, &local_env)? {
+        ControlFlow::Return(value) => Ok(value),
+        // The parser keeps bweak and continyu inside loops, so only Normal is left
+        _ => Ok(Value::Nil),
+    }
+}
+
+/// Like `kind_name`, except that records, instances and variants give the name of their `bento`, `kwass` or `flavor`.
+fn evaluate_kind_of(k: &KindOfNode, env: &Env) -> Result<Value, RuntimeError> {
+    let kind = match evaluate(&k.expression, env)? {
+        Value::Record(record) => record.borrow().record_type.name.clone(),
+        Value::Instance(instance) => instance.borrow().class.name.clone(),
+        Value::Variant(variant) => variant.enum_type.name.clone(),
+        other => kind_name(&other).to_string(),
+    };
+    Ok(Value::String(kind))
+}
+
+pub(super) fn kind_name(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "string",
+        Value::Int(_) | Value::BigInt(_) => "int",
+        Value::Float(_) => "float",
+        Value::Decimal(_) => "decimal",
+        Value::Bool(_) => "bool",
+        Value::Nil => "nuww",
+        Value::Function(_) | Value::Builtin(_) | Value::VariantConstructor(..) => "function",
+        Value::List(_) => "list",
+        Value::Map(_) => "map",
+        Value::RecordType(_) => "bento",
+        Value::Record(_) => "record",
+        Value::Class(_) => "kwass",
+        Value::Instance(_) => "instance",
+        Value::Enum(_) => "flavor",
+        Value::Variant(_) => "variant",
+    }
+}
+
+/// `target.member`: a field of a record or a variant, a field or method of an instance, or a variant of an enum.
+/// Methods come out bound, `obj.method` remembers `obj` even when stored for later.
+fn evaluate_member_access(access: &MemberAccessNode, env: &Env) -> Result<Value, RuntimeError> {
+    match evaluate(&access.target, env)? {
+        Value::Record(record) => {
+            let record = record.borrow();
+            match record.field_index(&access.member) {
+                Some(i) => Ok(record.values[i].clone()),
+                None => Err(unknown_field(&record.record_type.name, access)),
+            }
+        }
+        Value::Instance(instance) => {
+            let object = instance.borrow();
+            if let Some(value) = object.field(&access.member) {
+                return Ok(value.clone());
+            }
+            match object.class.find_method(&access.member) {
+                Some((method, class)) => Ok(Value::Function(method.bind(&instance, class))),
+                None => Err(unknown_field(&object.class.name, access)),
+            }
         }
+        Value::Variant(variant) => {
+            let declared = variant.declared();
+            match declared.fields.iter().position(|f| *f == access.member) {
+                Some(i) => Ok(variant.values[i].clone()),
+                None => Err(unknown_field(&format!("{}.{}", variant.enum_type.name, declared.name), access)),
+            }
+        }
+        // A variant without fields is a value already, the others need their fields first
+        Value::Enum(enum_type) => match enum_type.variant_index(&access.member) {
+            Some(i) if enum_type.variants[i].fields.is_empty() => Ok(Value::Variant(Rc::new(Variant {
+                enum_type: Rc::clone(&enum_type),
+                index: i,
+                values: Vec::new(),
+            }))),
+            Some(i) => Ok(Value::VariantConstructor(enum_type, i)),
+            None => Err(RuntimeError::UnknownVariant {
+                enum_name: enum_type.name.clone(),
+                variant: access.member.clone(),
+                span: access.span,
+            }),
+        },
+        other => Err(no_fields(&other, access)),
     }
+}
 
-    return_value.unwrap_or(Value::String("No return value".to_string()))
+fn no_fields(value: &Value, access: &MemberAccessNode) -> RuntimeError {
+    RuntimeError::NoFields {
+        kind: kind_name(value),
+        span: access.target.span(),
+    }
 }
 
-fn evaluate_kind_of(k: &KindOfNode, env: &Environment) -> Value {
-    let result = evaluate(&k.expression, env);
-    match result {
-        Value::String(_) => Value::String("string".to_string()),
-        Value::Number(_) => Value::String("number".to_string()),
-        Value::Bool(_) => Value::String("bool".to_string()),
+fn unknown_field(type_name: &str, access: &MemberAccessNode) -> RuntimeError {
+    RuntimeError::UnknownField {
+        type_name: type_name.to_string(),
+        field: access.member.clone(),
+        span: access.span,
+    }
+}
+
+/// Checks that `key` can be used in a map.
+pub(super) fn map_key(key: Value, span: Span) -> Result<Value, RuntimeError> {
+    match key {
+        Value::String(_) | Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Decimal(_) | Value::Bool(_) => Ok(key),
+        other => Err(RuntimeError::InvalidKey {
+            kind: kind_name(&other),
+            span,
+        }),
+    }
+}
+
+/// Reads an index, which has to be an int. Ints too big for 64 bits are out of range anyway,
+/// they are brought back to the largest (or smallest) one.
+pub(super) fn whole_number(index: &Value, span: Span) -> Result<i64, RuntimeError> {
+    match index {
+        Value::Int(n) => Ok(*n),
+        Value::BigInt(n) if n.sign() == Sign::Minus => Ok(i64::MIN),
+        Value::BigInt(_) => Ok(i64::MAX),
+        _ => Err(RuntimeError::InvalidIndex { span }),
+    }
+}
+
+/// Turns `index` into a position among `length` items. Negative indices count from the end,
+/// `-1` being the last item.
+pub(super) fn checked_index(index: &Value, length: usize, span: Span) -> Result<usize, RuntimeError> {
+    let index = whole_number(index, span)?;
+    let position = if index < 0 { index + length as i64 } else { index };
+    if position < 0 || position >= length as i64 {
+        return Err(RuntimeError::IndexOutOfBounds { index, length, span });
+    }
+    Ok(position as usize)
+}
+
+/// `target[start..end]` on a list or a string. Bounds past either end are clamped like in Python,
+/// so slicing never fails because of them.
+fn evaluate_slice(slice: &SliceNode, env: &Env) -> Result<Value, RuntimeError> {
+    let target = evaluate(&slice.target, env)?;
+    let length = match &target {
+        Value::List(items) => items.borrow().len(),
+        Value::String(s) => s.chars().count(),
+        other => {
+            return Err(RuntimeError::NotIndexable {
+                kind: kind_name(other),
+                span: slice.target.span(),
+            });
+        }
+    };
+
+    let bound = |node: &Option<Box<ASTNode>>, default: usize| -> Result<usize, RuntimeError> {
+        let Some(node) = node else {
+            return Ok(default);
+        };
+        let index = whole_number(&evaluate(node, env)?, node.span())?;
+        let position = if index < 0 { index + length as i64 } else { index };
+        Ok(position.clamp(0, length as i64) as usize)
+    };
+    let start = bound(&slice.start, 0)?;
+    let end = bound(&slice.end, length)?.max(start);
+
+    match target {
+        Value::List(items) => Ok(Value::List(Rc::new(RefCell::new(items.borrow()[start..end].to_vec())))),
+        Value::String(s) => Ok(Value::String(s.chars().skip(start).take(end - start).collect())),
+        _ => unreachable!("only lists and strings get past the check above"),
+    }
+}
+
+/// Values a `fow` loop goes through: the numbers of a range, the items of a list,
+/// the keys of a map, or the characters of a string.
+fn iterate(iterable: &ASTNode, env: &Env) -> Result<Box<dyn Iterator<Item = Value>>, RuntimeError> {
+    if let ASTNode::Range(range) = iterable {
+        let bound = |node: &ASTNode| match evaluate(node, env)? {
+            Value::Bool(_) => None,
+            value => as_number(&value),
+        }
+        .ok_or_else(|| RuntimeError::InvalidRange {
+            reason: "range bounds and step must be numbers",
+            span: node.span(),
+        });
+
+        let start = bound(&range.start)?;
+        let end = bound(&range.end)?;
+        let step = match &range.step {
+            Some(step) => bound(step)?,
+            None => Number::Int(1),
+        };
+        if step.to_float() == 0.0 {
+            return Err(RuntimeError::InvalidRange {
+                reason: "the step can't be 0",
+                span: range.span,
+            });
+        }
+
+        // Ints give ints, decimals give decimals, a float anywhere makes the whole range floats
+        if let (Number::Int(start), Number::Int(end), Number::Int(step)) = (&start, &end, &step) {
+            let (end, step) = (*end, *step);
+            let values = std::iter::successors(Some(*start), move |n| n.checked_add(step))
+                .take_while(move |n| if step > 0 { *n < end } else { *n > end })
+                .map(Value::Int);
+            return Ok(Box::new(values));
+        }
+        if let (Some(start), Some(end), Some(step)) = (start.to_big(), end.to_big(), step.to_big()) {
+            let ascending = step.sign() == Sign::Plus;
+            let values = std::iter::successors(Some(start), move |n| Some(n + &step))
+                .take_while(move |n| if ascending { *n < end } else { *n > end })
+                .map(int_value);
+            return Ok(Box::new(values));
+        }
+        if let (Some(start), Some(end), Some(step)) = (start.to_decimal(), end.to_decimal(), step.to_decimal()) {
+            let ascending = !step.is_negative();
+            let values = std::iter::successors(Some(start), move |n| Some(n + &step))
+                .take_while(move |n| if ascending { *n < end } else { *n > end })
+                .map(Value::Decimal);
+            return Ok(Box::new(values));
+        }
+
+        let (start, end, step) = (start.to_float(), end.to_float(), step.to_float());
+        // Multiplying instead of adding keeps float steps from drifting
+        let values = (0u64..)
+            .map(move |i| start + i as f64 * step)
+            .take_while(move |n| if step > 0.0 { *n < end } else { *n > end })
+            .map(Value::Float);
+        return Ok(Box::new(values));
+    }
+
+    match evaluate(iterable, env)? {
+        // Going through a copy, so changing the list inside the loop doesn't affect it
+        Value::List(items) => Ok(Box::new(items.borrow().clone().into_iter())),
+        Value::Map(map) => Ok(Box::new(map.borrow().keys().into_iter())),
+        Value::String(s) => {
+            let chars: Vec<Value> = s.chars().map(|c| Value::String(c.to_string())).collect();
+            Ok(Box::new(chars.into_iter()))
+        }
+        value => Err(RuntimeError::NotIterable {
+            kind: kind_name(&value),
+            span: iterable.span(),
+        }),
+    }
+}
+
+fn bool_value(value: bool) -> Value {
+    Value::Bool(value as i8)
+}
+
+/// Values of different kinds are never equal, `1 == twue` is `fawse`, except numbers which compare by value.
+/// Lists are equal when their items are, maps when they have the same entries in any order,
+/// and records or variants when they come from the same `bento` or variant with equal fields.
+/// Instances are only equal to themselves.
+fn values_equal(left: &Value, right: &Value) -> bool {
+    equal_within(left, right, &mut Vec::new())
+}
+
+/// `comparing` holds the lists, maps and records being compared further up, in pairs. Meeting a
+/// pair again means going around a cycle, which can't make them differ, so it counts as equal.
+fn equal_within(left: &Value, right: &Value, comparing: &mut Vec<(*const (), *const ())>) -> bool {
+    match (left, right) {
+        (Value::Int(l), Value::Int(r)) => l == r,
+        (
+            Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Decimal(_),
+            Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Decimal(_),
+        ) => as_number(left)
+            .zip(as_number(right))
+            .and_then(|(l, r)| compare_numbers(&l, &r))
+            .is_some_and(|ordering| ordering.is_eq()),
+        (Value::String(l), Value::String(r)) => l == r,
+        (Value::Bool(l), Value::Bool(r)) => l == r,
+        (Value::Nil, Value::Nil) => true,
+        (Value::Function(l), Value::Function(r)) => Rc::ptr_eq(l, r),
+        (Value::Builtin(l), Value::Builtin(r)) => std::ptr::eq(*l, *r),
+        (Value::List(l), Value::List(r)) => compare_once(l, r, comparing, |comparing| {
+            let (l, r) = (l.borrow(), r.borrow());
+            l.len() == r.len() && l.iter().zip(r.iter()).all(|(a, b)| equal_within(a, b, comparing))
+        }),
+        (Value::RecordType(l), Value::RecordType(r)) => Rc::ptr_eq(l, r),
+        (Value::Class(l), Value::Class(r)) => Rc::ptr_eq(l, r),
+        (Value::Instance(l), Value::Instance(r)) => Rc::ptr_eq(l, r),
+        (Value::Enum(l), Value::Enum(r)) => Rc::ptr_eq(l, r),
+        (Value::VariantConstructor(l, i), Value::VariantConstructor(r, j)) => Rc::ptr_eq(l, r) && i == j,
+        // Variants can't change, so they can only be part of a cycle through a list, a map or a record
+        (Value::Variant(l), Value::Variant(r)) => {
+            Rc::ptr_eq(&l.enum_type, &r.enum_type)
+                && l.index == r.index
+                && l.values.iter().zip(&r.values).all(|(a, b)| equal_within(a, b, comparing))
+        }
+        (Value::Record(l), Value::Record(r)) => compare_once(l, r, comparing, |comparing| {
+            let (l, r) = (l.borrow(), r.borrow());
+            Rc::ptr_eq(&l.record_type, &r.record_type)
+                && l.values.iter().zip(&r.values).all(|(a, b)| equal_within(a, b, comparing))
+        }),
+        (Value::Map(l), Value::Map(r)) => compare_once(l, r, comparing, |comparing| {
+            let (l, r) = (l.borrow(), r.borrow());
+            l.len() == r.len()
+                && l.entries.iter().all(|(key, value)| {
+                    r.get(key).is_some_and(|other| equal_within(value, other, comparing))
+                })
+        }),
+        _ => false,
+    }
+}
+
+/// Runs `compare` on two shared values, unless they are the same one or already being compared.
+fn compare_once<T>(
+    l: &Rc<T>,
+    r: &Rc<T>,
+    comparing: &mut Vec<(*const (), *const ())>,
+    compare: impl FnOnce(&mut Vec<(*const (), *const ())>) -> bool,
+) -> bool {
+    let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+    if Rc::ptr_eq(l, r) || comparing.contains(&pair) {
+        return true;
+    }
+
+    comparing.push(pair);
+    let equal = compare(comparing);
+    comparing.pop();
+    equal
+}
+
+/// Orders numbers (bools count as 0 and 1, like in arithmetic) and strings.
+/// Returns `None` for anything else, including NaN.
+fn compare_values(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
+    if let (Value::String(l), Value::String(r)) = (left, right) {
+        return Some(l.cmp(r));
+    }
+    compare_numbers(&as_number(left)?, &as_number(right)?)
+}
+
+/// Conditions accept any value: `fawse`, `nuww`, `0`, `""`, `[]` and `{}` are falsy, everything else is truthy.
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b != 0,
+        Value::Nil => false,
+        Value::Int(n) => *n != 0,
+        Value::BigInt(_) => true, // Zero always fits in an `Int`
+        Value::Float(n) => *n != 0.0,
+        Value::Decimal(n) => !n.is_zero(),
+        Value::String(s) => !s.is_empty(),
+        Value::List(items) => !items.borrow().is_empty(),
+        Value::Map(map) => map.borrow().len() > 0,
+        Value::Function(_)
+        | Value::Builtin(_)
+        | Value::RecordType(_)
+        | Value::Record(_)
+        | Value::Class(_)
+        | Value::Instance(_)
+        | Value::Enum(_)
+        | Value::VariantConstructor(..)
+        | Value::Variant(_) => true,
     }
 }
 
 // === Execution ===
 
-fn execute(node: &ASTNode, env: &mut Environment) -> Option<Value> {
+fn execute_assignment(assignment: &AssignmentNode, env: &Env) -> Result<(), RuntimeError> {
+    match assignment.target.as_ref() {
+        ASTNode::VariableReference(var) => assign_to_variable(var, assignment, env),
+        ASTNode::Index(index) => assign_to_item(index, assignment, env),
+        ASTNode::MemberAccess(access) => assign_to_field(access, assignment, env),
+        other => Err(RuntimeError::CannotEvaluate { span: other.span() }),
+    }
+}
+
+fn assign_to_variable(var: &VariableReferenceNode, assignment: &AssignmentNode, env: &Env) -> Result<(), RuntimeError> {
+    let undeclared = || RuntimeError::UndeclaredAssignment {
+        name: var.name.clone(),
+        span: assignment.span,
+    };
+
+    let mut value = evaluate(&assignment.value, env)?;
+    if let Some(operator) = &assignment.operator {
+        let current = env.borrow().get_variable(&var.name).ok_or_else(undeclared)?;
+        value = apply_binary_operator(operator, &current, &value, assignment.span)?;
+    }
+
+    let result = env.borrow_mut().assign_variable(&var.name, value);
+    match result {
+        Assignment::Done => Ok(()),
+        Assignment::Undeclared => Err(undeclared()),
+        Assignment::Constant => Err(RuntimeError::ConstantAssignment {
+            name: var.name.clone(),
+            span: assignment.span,
+        }),
+    }
+}
+
+/// `xs[i] = value` or `m[key] = value`. Strings can be indexed but not changed in place.
+fn assign_to_item(index: &IndexNode, assignment: &AssignmentNode, env: &Env) -> Result<(), RuntimeError> {
+    let target = evaluate(&index.target, env)?;
+    let position = evaluate(&index.index, env)?;
+    let mut value = evaluate(&assignment.value, env)?;
+
+    let items = match target {
+        Value::List(items) => items,
+        Value::Map(map) => {
+            let key = map_key(position, index.index.span())?;
+            if let Some(operator) = &assignment.operator {
+                let current = map.borrow().get(&key).cloned().ok_or_else(|| RuntimeError::KeyNotFound {
+                    key: quoted(&key),
+                    span: index.span,
+                })?;
+                value = apply_binary_operator(operator, &current, &value, assignment.span)?;
+            }
+            map.borrow_mut().insert(key, value);
+            return Ok(());
+        }
+        Value::String(_) => {
+            return Err(RuntimeError::NotAssignable {
+                kind: "string",
+                span: index.span,
+            });
+        }
+        other => {
+            return Err(RuntimeError::NotIndexable {
+                kind: kind_name(&other),
+                span: index.target.span(),
+            });
+        }
+    };
+
+    // Checked after the value ran, in case it changed the list
+    let i = checked_index(&position, items.borrow().len(), index.span)?;
+    if let Some(operator) = &assignment.operator {
+        let current = items.borrow()[i].clone();
+        value = apply_binary_operator(operator, &current, &value, assignment.span)?;
+    }
+    items.borrow_mut()[i] = value;
+    Ok(())
+}
+
+/// `target.field = value`. Records only have the fields listed in their `bento`,
+/// instances get a new field when it doesn't exist yet.
+fn assign_to_field(access: &MemberAccessNode, assignment: &AssignmentNode, env: &Env) -> Result<(), RuntimeError> {
+    let target = evaluate(&access.target, env)?;
+    let mut value = evaluate(&assignment.value, env)?;
+
+    match &target {
+        Value::Record(record) => {
+            let mut record = record.borrow_mut();
+            let i = record
+                .field_index(&access.member)
+                .ok_or_else(|| unknown_field(&record.record_type.name, access))?;
+            if let Some(operator) = &assignment.operator {
+                value = apply_binary_operator(operator, &record.values[i], &value, assignment.span)?;
+            }
+            record.values[i] = value;
+        }
+        Value::Instance(instance) => {
+            let mut object = instance.borrow_mut();
+            if let Some(operator) = &assignment.operator {
+                let current = object
+                    .field(&access.member)
+                    .ok_or_else(|| unknown_field(&object.class.name, access))?;
+                value = apply_binary_operator(operator, current, &value, assignment.span)?;
+            }
+            object.set_field(&access.member, value);
+        }
+        Value::Variant(_) => return Err(RuntimeError::ImmutableVariant { span: access.span }),
+        other => return Err(no_fields(other, access)),
+    }
+    Ok(())
+}
+
+/// Classes are not hoisted like functions: the parent has to exist by the time the `kwass` line runs.
+fn execute_class_declaration(class: &ClassDeclarationNode, env: &Env) -> Result<(), RuntimeError> {
+    let superclass = match &class.superclass {
+        Some(parent) => match evaluate(parent, env)? {
+            Value::Class(parent) => Some(parent),
+            other => {
+                return Err(RuntimeError::NotAClass {
+                    kind: kind_name(&other),
+                    span: parent.span(),
+                });
+            }
+        },
+        None => None,
+    };
+
+    let methods = class
+        .methods
+        .iter()
+        .map(|method| {
+            let function = Function::create(&method.name, &method.params, &method.body, env);
+            (method.name.clone(), function)
+        })
+        .collect();
+
+    let value = Value::Class(Rc::new(Class {
+        name: class.name.clone(),
+        superclass,
+        methods,
+    }));
+    declare(env, &class.name, value, None, class.span)
+}
+
+/// Runs the body of the first arm whose pattern matches `subject` and whose guard passes, giving back
+/// what `run_body` made of it, or `None` when no arm fits. Each arm runs in its own scope holding
+/// the names its pattern bound.
+fn run_match<T>(
+    match_node: &MatchNode,
+    subject: &Value,
+    env: &Env,
+    run_body: impl Fn(&MatchBody, &Env) -> Result<T, RuntimeError>,
+) -> Result<Option<T>, RuntimeError> {
+    for arm in &match_node.arms {
+        let mut bindings = Vec::new();
+        if !match_pattern(&arm.pattern, subject, env, &mut bindings)? {
+            continue;
+        }
+
+        let arm_env = Environment::new_child(env);
+        arm_env.borrow_mut().variables.extend(bindings);
+
+        let passed = match &arm.guard {
+            Some(guard) => is_truthy(&evaluate(guard, &arm_env)?),
+            None => true,
+        };
+        if passed {
+            return run_body(&arm.body, &arm_env).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Checks `value` against `pattern`, collecting the names it binds along the way.
+fn match_pattern(
+    pattern: &Pattern,
+    value: &Value,
+    env: &Env,
+    bindings: &mut Vec<(String, Value)>,
+) -> Result<bool, RuntimeError> {
+    match pattern {
+        Pattern::Wildcard => Ok(true),
+        Pattern::Binding(name) => {
+            bindings.push((name.clone(), value.clone()));
+            Ok(true)
+        }
+        Pattern::Literal(literal) => Ok(values_equal(&evaluate(literal, env)?, value)),
+        Pattern::Variant { enum_name, variant, fields, span } => {
+            let found = env.borrow().get_variable(enum_name);
+            let enum_type = match found {
+                Some(Value::Enum(enum_type)) => enum_type,
+                Some(other) => {
+                    return Err(RuntimeError::NotAnEnum {
+                        kind: kind_name(&other),
+                        span: *span,
+                    });
+                }
+                None => {
+                    return Err(RuntimeError::UndefinedVariable {
+                        name: enum_name.clone(),
+                        span: *span,
+                    });
+                }
+            };
+            let index = enum_type.variant_index(variant).ok_or_else(|| RuntimeError::UnknownVariant {
+                enum_name: enum_name.clone(),
+                variant: variant.clone(),
+                span: *span,
+            })?;
+
+            let Value::Variant(candidate) = value else {
+                return Ok(false);
+            };
+            if !Rc::ptr_eq(&candidate.enum_type, &enum_type) || candidate.index != index {
+                return Ok(false);
+            }
+
+            // Without parentheses, the pattern matches the variant whatever it carries
+            let Some(fields) = fields else {
+                return Ok(true);
+            };
+            if fields.len() != candidate.values.len() {
+                return Err(RuntimeError::PatternArity {
+                    variant: format!("{}.{}", enum_name, variant),
+                    expected: candidate.values.len(),
+                    found: fields.len(),
+                    span: *span,
+                });
+            }
+            for (field, value) in fields.iter().zip(&candidate.values) {
+                if !match_pattern(field, value, env, bindings)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+    }
+}
+
+/// Declares `name` in the current scope, failing if a `fowever` constant is in the way.
+fn declare(env: &Env, name: &str, value: Value, constant: Option<Span>, span: Span) -> Result<(), RuntimeError> {
+    let mut scope = env.borrow_mut();
+    if scope.declare_variable(name, value, constant) {
+        return Ok(());
+    }
+
+    let name = name.to_string();
+    if scope.is_constant(&name) {
+        Err(RuntimeError::ConstantRedeclaration { name, span })
+    } else {
+        Err(RuntimeError::AlreadyDeclared { name, span })
+    }
+}
+
+/// Runs statements in order, stopping early as soon as one of them returns.
+fn execute_block(body: &[ASTNode], env: &Env) -> Result<ControlFlow, RuntimeError> {
+    // Functions, record types and enums are declared first so they can be used above their definition
+    for stmt in body {
+        match stmt {
+            ASTNode::FunctionDeclaration(func) => {
+                let value = Function::create_value(&func.name, &func.params, &func.body, env);
+                declare(env, &func.name, value, None, func.span)?;
+            }
+            ASTNode::RecordDeclaration(record) => {
+                let value = Value::RecordType(Rc::new(RecordType {
+                    name: record.name.clone(),
+                    fields: record.fields.clone(),
+                }));
+                declare(env, &record.name, value, None, record.span)?;
+            }
+            ASTNode::EnumDeclaration(enum_node) => {
+                let value = Value::Enum(Rc::new(EnumType {
+                    name: enum_node.name.clone(),
+                    variants: enum_node.variants.clone(),
+                }));
+                declare(env, &enum_node.name, value, None, enum_node.span)?;
+            }
+            _ => {}
+        }
+    }
+
+    for stmt in body {
+        let flow = execute(stmt, env)?;
+        if !matches!(flow, ControlFlow::Normal) {
+            return Ok(flow);
+        }
+    }
+
+    Ok(ControlFlow::Normal)
+}
+
+fn execute(node: &ASTNode, env: &Env) -> Result<ControlFlow, RuntimeError> {
     match node {
         ASTNode::Print(p) => {
-            let result = evaluate(&p.expression, env);
-            match result {
-                Value::String(s) => println!("{}", s),
-                Value::Number(n) => println!("{}", n),
-                Value::Bool(b) => {
-                    if b == 1 {
-                        println!("twue");
-                    } else {
-                        println!("fawse");
-                    }
-                },
-            }
-            None
+            let result = evaluate(&p.expression, env)?;
+            println!("{}", result);
+            Ok(ControlFlow::Normal)
         }
 
         ASTNode::OwO(p) => {
-            let result = evaluate(&p.expression, env); // Get the evaluated expression result
+            let result = evaluate(&p.expression, env)?; // Get the evaluated expression result
             match result {
                 Value::String(s) => {
                     let owo_result = owoify(&s); // Apply owoify to the string
                     println!("{}", owo_result);  // Print the "owoified" result
                 }
-                Value::Number(n) => {
-                    println!("{}", n);  // If the result is a number, print it as is
-                }
-                Value::Bool(b) => {
-                    if b == 1 {
-                        println!("twue");
-                    } else {
-                        println!("fawse");
-                    }
+                other => {
+                    println!("{}", other);  // Anything else is printed as is
                 }
-                // _ => panic!("OwO only supports strings or numbers."),
             }
-            None
+            Ok(ControlFlow::Normal)
         }
 
-        ASTNode::KindOf(k) => Some(evaluate_kind_of(k, env)),
+        ASTNode::KindOf(k) => {
+            evaluate_kind_of(k, env)?;
+            Ok(ControlFlow::Normal)
+        }
 
-        ASTNode::FunctionCall(call) => Some(evaluate_function_call(call, env)),
+        ASTNode::FunctionCall(call) => {
+            evaluate_function_call(call, env)?;
+            Ok(ControlFlow::Normal)
+        }
 
-        ASTNode::Return(ret) => Some(evaluate(&ret.value, env)),
+        ASTNode::Return(ret) => {
+            let value = match &ret.value {
+                Some(value) => evaluate(value, env)?,
+                None => Value::Nil,
+            };
+            Ok(ControlFlow::Return(value))
+        }
 
         ASTNode::VariableDeclaration(decl) => {
-            let value = evaluate(&decl.value, env);
-            env.variables.insert(decl.name.clone(), value);
-            None
+            let value = evaluate(&decl.value, env)?;
+            let constant = decl.constant.then_some(decl.span);
+            declare(env, &decl.name, value, constant, decl.span)?;
+            Ok(ControlFlow::Normal)
+        }
+
+        ASTNode::Assignment(assignment) => {
+            execute_assignment(assignment, env)?;
+            Ok(ControlFlow::Normal)
         }
 
-        _ => None,
+        ASTNode::If(if_node) => {
+            let condition = evaluate(&if_node.condition, env)?;
+            if is_truthy(&condition) {
+                execute_block(&if_node.body, env)
+            } else if let Some(else_body) = &if_node.else_body {
+                execute_block(else_body, env)
+            } else {
+                Ok(ControlFlow::Normal)
+            }
+        }
+
+        ASTNode::While(while_node) => {
+            while is_truthy(&evaluate(&while_node.condition, env)?) {
+                match execute_block(&while_node.body, env)? {
+                    ControlFlow::Break => break,
+                    ControlFlow::Continue | ControlFlow::Normal => {}
+                    flow @ ControlFlow::Return(_) => return Ok(flow),
+                }
+            }
+            Ok(ControlFlow::Normal)
+        }
+
+        ASTNode::For(for_node) => {
+            let values = iterate(&for_node.iterable, env)?;
+
+            // Each pass gets its own scope, so a closure made in the body keeps the value it saw.
+            // The loop variable can shadow a constant from outside, like any name in a nested scope
+            for value in values {
+                let iteration_env = Environment::new_child(env);
+                iteration_env.borrow_mut().variables.insert(for_node.variable.clone(), value);
+                match execute_block(&for_node.body, &iteration_env)? {
+                    ControlFlow::Break => break,
+                    ControlFlow::Continue | ControlFlow::Normal => {}
+                    flow @ ControlFlow::Return(_) => return Ok(flow),
+                }
+            }
+            Ok(ControlFlow::Normal)
+        }
+
+        ASTNode::ClassDeclaration(class) => {
+            execute_class_declaration(class, env)?;
+            Ok(ControlFlow::Normal)
+        }
+
+        ASTNode::Match(match_node) => {
+            let subject = evaluate(&match_node.subject, env)?;
+            let flow = run_match(match_node, &subject, env, |body, arm_env| match body {
+                MatchBody::Block(body) => execute_block(body, arm_env),
+                MatchBody::Expression(expr) => evaluate(expr, arm_env).map(|_| ControlFlow::Normal),
+            })?;
+            Ok(flow.unwrap_or(ControlFlow::Normal))
+        }
+
+        ASTNode::Break(_) => Ok(ControlFlow::Break),
+
+        ASTNode::Continue(_) => Ok(ControlFlow::Continue),
+
+        // Already declared by `execute_block` before the statements run
+        ASTNode::FunctionDeclaration(_) | ASTNode::RecordDeclaration(_) | ASTNode::EnumDeclaration(_) => {
+            Ok(ControlFlow::Normal)
+        }
+
+        // Any other expression runs for what it does (or the error it raises), its value is dropped
+        _ => {
+            evaluate(node, env)?;
+            Ok(ControlFlow::Normal)
+        }
     }
 }
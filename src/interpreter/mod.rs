@@ -1 +1,5 @@
-pub mod interpreter;
\ No newline at end of file
+pub mod builtins;
+pub mod errors;
+#[allow(clippy::module_inception)]
+pub mod interpreter;
+pub mod numbers;
@@ -0,0 +1,400 @@
+use std::cmp::Ordering;
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::interpreter::errors::RuntimeError;
+use crate::interpreter::interpreter::Value;
+use crate::utils::decimal::{Decimal, floor_div_rem_big};
+use crate::utils::diagnostics::Span;
+
+/// A number taking part in arithmetic, bools count as the ints 0 and 1.
+/// Ints are `Int` while they fit in 64 bits and `Big` past that, scripts only ever see "int".
+#[derive(Debug, Clone)]
+pub(super) enum Number {
+    Int(i64),
+    Big(BigInt),
+    Float(f64),
+    Decimal(Decimal),
+}
+
+impl Number {
+    pub(super) fn to_float(&self) -> f64 {
+        match self {
+            Number::Int(n) => *n as f64,
+            Number::Big(n) => n.to_f64().unwrap_or(f64::NAN),
+            Number::Float(n) => *n,
+            Number::Decimal(n) => n.to_f64(),
+        }
+    }
+
+    /// The number as a big int, `None` for floats and decimals.
+    pub(super) fn to_big(&self) -> Option<BigInt> {
+        match self {
+            Number::Int(n) => Some(BigInt::from(*n)),
+            Number::Big(n) => Some(n.clone()),
+            Number::Float(_) | Number::Decimal(_) => None,
+        }
+    }
+
+    /// The number as an exact decimal, `None` for floats.
+    pub(super) fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            Number::Decimal(n) => Some(n.clone()),
+            Number::Float(_) => None,
+            _ => self.to_big().map(Decimal::from_int),
+        }
+    }
+}
+
+pub(super) fn as_number(value: &Value) -> Option<Number> {
+    match value {
+        Value::Int(n) => Some(Number::Int(*n)),
+        Value::BigInt(n) => Some(Number::Big(n.clone())),
+        Value::Float(n) => Some(Number::Float(*n)),
+        Value::Decimal(n) => Some(Number::Decimal(n.clone())),
+        Value::Bool(b) => Some(Number::Int(*b as i64)),
+        _ => None,
+    }
+}
+
+/// Makes an int value, going back to a plain `Int` when it fits.
+pub(super) fn int_value(n: BigInt) -> Value {
+    match i64::try_from(&n) {
+        Ok(small) => Value::Int(small),
+        Err(_) => Value::BigInt(n),
+    }
+}
+
+/// `+`, `-`, `*`, `/`, `//` and `%` on numbers, `None` when an operand isn't one. Ints give ints,
+/// `/` included: it drops the remainder, so `7 / 2` is `3`. They never overflow, they grow as big
+/// as needed instead. A decimal with an int gives a decimal, worked out exactly except for `/`,
+/// which rounds. With a float on either side, the result is a float.
+///
+/// `//` and `%` round toward minus infinity rather than zero, so `-7 // 2` is `-4` and the
+/// remainder takes the sign of the right side: `-7 % 2` is `1`.
+pub(super) fn arithmetic(
+    operator: &str,
+    left: &Value,
+    right: &Value,
+    span: Span,
+) -> Result<Option<Value>, RuntimeError> {
+    let (Some(left), Some(right)) = (as_number(left), as_number(right)) else {
+        return Ok(None);
+    };
+
+    // Plain ints are tried first, the big ones only step in when that overflows (or divides by 0)
+    if let (Number::Int(l), Number::Int(r)) = (&left, &right) {
+        let result = match operator {
+            "+" => l.checked_add(*r),
+            "-" => l.checked_sub(*r),
+            "*" => l.checked_mul(*r),
+            "//" => floor_div_rem(*l, *r).map(|(quotient, _)| quotient),
+            "%" => floor_div_rem(*l, *r).map(|(_, remainder)| remainder),
+            _ => l.checked_div(*r),
+        };
+        if let Some(result) = result {
+            return Ok(Some(Value::Int(result)));
+        }
+    }
+
+    let result = match (left.to_big(), right.to_big()) {
+        (Some(l), Some(r)) => {
+            if matches!(operator, "/" | "//" | "%") && r.is_zero() {
+                return Err(RuntimeError::DivisionByZero { span });
+            }
+            int_value(match operator {
+                "+" => l + r,
+                "-" => l - r,
+                "*" => l * r,
+                "//" => floor_div_rem_big(&l, &r).0,
+                "%" => floor_div_rem_big(&l, &r).1,
+                _ => l / r,
+            })
+        }
+        _ => match (left.to_decimal(), right.to_decimal()) {
+            (Some(l), Some(r)) => Value::Decimal(match operator {
+                "+" => &l + &r,
+                "-" => &l - &r,
+                "*" => &l * &r,
+                "//" | "%" => {
+                    let (quotient, remainder) = l.floor_div_rem(&r).ok_or(RuntimeError::DivisionByZero { span })?;
+                    if operator == "//" { quotient } else { remainder }
+                }
+                _ => l.checked_div(&r).ok_or(RuntimeError::DivisionByZero { span })?,
+            }),
+            _ => {
+                let (l, r) = (left.to_float(), right.to_float());
+                Value::Float(match operator {
+                    "+" => l + r,
+                    "-" => l - r,
+                    "*" => l * r,
+                    "//" => (l / r).floor(),
+                    "%" => {
+                        let remainder = l % r;
+                        if remainder != 0.0 && (remainder < 0.0) != (r < 0.0) { remainder + r } else { remainder }
+                    }
+                    _ => l / r,
+                })
+            }
+        },
+    };
+    Ok(Some(result))
+}
+
+/// `base ** exponent`. Ints to a power of 0 or more give ints, and decimals to a whole power give
+/// exact decimals (a negative power divides, rounding like `/`). Anything else, like `2 ** -1`
+/// or `2 ** 0.5`, gives a float.
+pub(super) fn power(left: &Value, right: &Value, span: Span) -> Result<Option<Value>, RuntimeError> {
+    let (Some(base), Some(exponent)) = (as_number(left), as_number(right)) else {
+        return Ok(None);
+    };
+    let too_big = || RuntimeError::NumberTooBig { span };
+
+    if let (Some(base), Some(exponent)) = (base.to_big(), exponent.to_big())
+        && exponent.sign() != Sign::Minus
+    {
+        return int_power(&base, &exponent).map(int_value).ok_or_else(too_big).map(Some);
+    }
+
+    // Only a decimal on either side keeps the result exact, an int to a negative power is a float
+    let has_decimal = matches!(base, Number::Decimal(_)) || matches!(exponent, Number::Decimal(_));
+    let whole_exponent = match &exponent {
+        Number::Decimal(n) if n.is_whole() => Some(n.trunc()),
+        _ => exponent.to_big(),
+    };
+    if let (true, Some(base), Some(exponent)) = (has_decimal, base.to_decimal(), whole_exponent) {
+        let result = if base.is_whole() {
+            int_power(&base.trunc(), &exponent.abs()).map(Decimal::from_int)
+        } else {
+            // Each digit takes a bit over 3 bits, so this is about how long the result gets
+            let length = base.to_string().len() as u64 * 4;
+            exponent.abs().to_u32().filter(|e| length * *e as u64 <= MAX_BITS).map(|e| base.pow(e))
+        }
+        .ok_or_else(too_big)?;
+
+        if exponent.sign() != Sign::Minus {
+            return Ok(Some(Value::Decimal(result)));
+        }
+        let one = Decimal::from_int(BigInt::from(1));
+        return one.checked_div(&result).map(|n| Some(Value::Decimal(n))).ok_or(RuntimeError::DivisionByZero { span });
+    }
+
+    Ok(Some(Value::Float(base.to_float().powf(exponent.to_float()))))
+}
+
+/// `&`, `|`, `^`, `<<` and `>>` on ints, `None` when an operand isn't an int or a bool. Bools count
+/// as 0 and 1, except that two bools with `&`, `|` or `^` give a bool back, so they also work as
+/// logic that always runs both sides. `>>` rounds toward minus infinity, `-5 >> 1` is `-3`.
+pub(super) fn bitwise(operator: &str, left: &Value, right: &Value, span: Span) -> Result<Option<Value>, RuntimeError> {
+    if let (Value::Bool(l), Value::Bool(r)) = (left, right)
+        && matches!(operator, "&" | "|" | "^")
+    {
+        let (l, r) = (*l != 0, *r != 0);
+        let result = match operator {
+            "&" => l & r,
+            "|" => l | r,
+            _ => l ^ r,
+        };
+        return Ok(Some(Value::Bool(result as i8)));
+    }
+
+    let whole = |value: &Value| match value {
+        Value::Float(_) | Value::Decimal(_) => None,
+        value => as_number(value)?.to_big(),
+    };
+    let (Some(l), Some(r)) = (whole(left), whole(right)) else {
+        return Ok(None);
+    };
+
+    let result = match operator {
+        "&" => l & r,
+        "|" => l | r,
+        "^" => l ^ r,
+        _ => {
+            if r.sign() == Sign::Minus {
+                return Err(RuntimeError::NegativeShift { span });
+            }
+            match operator {
+                "<<" if l.is_zero() => l,
+                "<<" => {
+                    let amount = r.to_u64().filter(|amount| l.bits().checked_add(*amount).is_some_and(|bits| bits <= MAX_BITS));
+                    l << amount.ok_or(RuntimeError::NumberTooBig { span })?
+                }
+                // Shifting further than the number is long leaves only its sign
+                _ => match r.to_u64().filter(|amount| *amount <= l.bits()) {
+                    Some(amount) => l >> amount,
+                    None if l.sign() == Sign::Minus => BigInt::from(-1),
+                    None => BigInt::zero(),
+                },
+            }
+        }
+    };
+    Ok(Some(int_value(result)))
+}
+
+/// `-n`, `None` when `n` isn't a number. Bools count as 0 and 1 like in arithmetic, so `-twue` is `-1`.
+pub(super) fn negate(value: &Value) -> Option<Value> {
+    Some(match as_number(value)? {
+        // `-i64::MIN` is one past the largest `Int`
+        Number::Int(n) => n.checked_neg().map_or_else(|| Value::BigInt(-BigInt::from(n)), Value::Int),
+        Number::Big(n) => int_value(-n),
+        Number::Float(n) => Value::Float(-n),
+        Number::Decimal(n) => Value::Decimal(-&n),
+    })
+}
+
+/// `+n`, which gives `n` back unchanged, or `1` and `0` for bools. `None` when `n` isn't a number.
+pub(super) fn unary_plus(value: &Value) -> Option<Value> {
+    Some(match as_number(value)? {
+        Number::Int(n) => Value::Int(n),
+        Number::Big(n) => Value::BigInt(n),
+        Number::Float(n) => Value::Float(n),
+        Number::Decimal(n) => Value::Decimal(n),
+    })
+}
+
+/// `~n`, which flips every bit of an int: `~n` is `-n - 1`. Bools don't count here, `not` is for them.
+pub(super) fn bitwise_not(value: &Value) -> Option<Value> {
+    match value {
+        Value::Int(n) => Some(Value::Int(!n)),
+        Value::BigInt(n) => Some(int_value(!n)),
+        _ => None,
+    }
+}
+
+/// Orders two numbers, `None` when one of them is NaN. Ints and decimals are compared exactly, whatever their size.
+pub(super) fn compare_numbers(left: &Number, right: &Number) -> Option<Ordering> {
+    if let (Number::Int(l), Number::Int(r)) = (left, right) {
+        return Some(l.cmp(r));
+    }
+    match (left.to_big(), right.to_big()) {
+        (Some(l), Some(r)) => Some(l.cmp(&r)),
+        _ => match (left.to_decimal(), right.to_decimal()) {
+            (Some(l), Some(r)) => Some(l.cmp(&r)),
+            _ => left.to_float().partial_cmp(&right.to_float()),
+        },
+    }
+}
+
+/// Largest number of bits `**` and `<<` will build (about 20 million digits), past that the
+/// script would spend minutes on a single number or run out of memory.
+const MAX_BITS: u64 = 1 << 26;
+
+/// `l // r` and `l % r`, `None` when `r` is 0 or the result doesn't fit.
+fn floor_div_rem(l: i64, r: i64) -> Option<(i64, i64)> {
+    let (quotient, remainder) = (l.checked_div(r)?, l.checked_rem(r)?);
+    if remainder != 0 && (remainder < 0) != (r < 0) {
+        return Some((quotient - 1, remainder + r));
+    }
+    Some((quotient, remainder))
+}
+
+fn int_power(base: &BigInt, exponent: &BigInt) -> Option<BigInt> {
+    // 0, 1 and -1 stay small whatever the exponent is
+    if base.magnitude() <= &BigUint::one() {
+        let odd = exponent.bit(0);
+        return Some(match base.sign() {
+            Sign::NoSign if exponent.is_zero() => BigInt::one(),
+            Sign::Minus if !odd => BigInt::one(),
+            _ => base.clone(),
+        });
+    }
+
+    let exponent = exponent.to_u32().filter(|e| base.bits() * *e as u64 <= MAX_BITS)?;
+    Some(base.pow(exponent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The result of an operator, shown with the kind of value it is: `int 5`, `big 2`, `float 0.5`...
+    fn run(operator: &str, left: Value, right: Value) -> Result<String, RuntimeError> {
+        let span = Span::new(1, 1, 1);
+        let result = match operator {
+            "**" => power(&left, &right, span)?,
+            "&" | "|" | "^" | "<<" | ">>" => bitwise(operator, &left, &right, span)?,
+            _ => arithmetic(operator, &left, &right, span)?,
+        };
+        Ok(match result.expect("both sides are numbers") {
+            Value::Int(n) => format!("int {}", n),
+            Value::BigInt(n) => format!("big {}", n),
+            Value::Float(n) => format!("float {}", n),
+            Value::Decimal(n) => format!("decimal {}", n),
+            other => format!("{:?}", other),
+        })
+    }
+
+    fn int(n: i64) -> Value {
+        Value::Int(n)
+    }
+
+    fn big(text: &str) -> Value {
+        Value::BigInt(text.parse().unwrap())
+    }
+
+    #[test]
+    fn ints_grow_past_64_bits() {
+        assert_eq!(run("+", int(i64::MAX), int(1)).unwrap(), "big 9223372036854775808");
+        assert_eq!(run("-", int(i64::MIN), int(1)).unwrap(), "big -9223372036854775809");
+        assert_eq!(run("*", int(i64::MAX), int(2)).unwrap(), "big 18446744073709551614");
+        assert_eq!(run("/", int(i64::MIN), int(-1)).unwrap(), "big 9223372036854775808");
+    }
+
+    #[test]
+    fn big_ints_shrink_back_when_they_fit() {
+        assert_eq!(run("-", big("9223372036854775808"), int(1)).unwrap(), "int 9223372036854775807");
+        assert_eq!(run("//", big("18446744073709551616"), int(4)).unwrap(), "int 4611686018427387904");
+        assert!(matches!(int_value(BigInt::from(-3)), Value::Int(-3)));
+        assert!(matches!(int_value(BigInt::from(i64::MAX) + 1), Value::BigInt(_)));
+    }
+
+    #[test]
+    fn floor_division_rounds_toward_minus_infinity() {
+        assert_eq!(run("/", int(-7), int(2)).unwrap(), "int -3");
+        assert_eq!(run("//", int(-7), int(2)).unwrap(), "int -4");
+        assert_eq!(run("%", int(-7), int(2)).unwrap(), "int 1");
+        assert_eq!(run("%", int(7), int(-2)).unwrap(), "int -1");
+        assert_eq!(run("%", int(i64::MIN), int(-1)).unwrap(), "int 0");
+        assert_eq!(run("%", Value::Float(-7.5), Value::Float(2.0)).unwrap(), "float 0.5");
+        assert!(matches!(run("//", int(1), int(0)), Err(RuntimeError::DivisionByZero { .. })));
+    }
+
+    #[test]
+    fn decimals_stay_exact() {
+        let dec = |text: &str| Value::Decimal(Decimal::parse(text).unwrap());
+        assert_eq!(run("+", dec("0.1"), dec("0.2")).unwrap(), "decimal 0.3");
+        assert_eq!(run("*", dec("0.5"), int(3)).unwrap(), "decimal 1.5");
+        assert_eq!(run("+", dec("0.5"), Value::Float(0.25)).unwrap(), "float 0.75");
+        assert!(matches!(run("/", dec("1"), int(0)), Err(RuntimeError::DivisionByZero { .. })));
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(run("**", int(2), int(10)).unwrap(), "int 1024");
+        assert_eq!(run("**", int(2), int(64)).unwrap(), "big 18446744073709551616");
+        assert_eq!(run("**", int(-1), big("18446744073709551617")).unwrap(), "int -1");
+        assert_eq!(run("**", int(2), int(-1)).unwrap(), "float 0.5");
+        assert!(matches!(run("**", int(2), big("18446744073709551616")), Err(RuntimeError::NumberTooBig { .. })));
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(run("<<", int(3), int(4)).unwrap(), "int 48");
+        assert_eq!(run("<<", int(1), int(64)).unwrap(), "big 18446744073709551616");
+        assert_eq!(run("<<", int(0), big("18446744073709551616")).unwrap(), "int 0");
+        assert_eq!(run(">>", int(-5), int(1)).unwrap(), "int -3");
+        assert_eq!(run(">>", int(-5), big("18446744073709551616")).unwrap(), "int -1");
+        assert_eq!(run(">>", int(5), int(100)).unwrap(), "int 0");
+        assert!(matches!(run("<<", int(1), int(-1)), Err(RuntimeError::NegativeShift { .. })));
+        assert!(matches!(run("<<", int(1), int(1 << 27)), Err(RuntimeError::NumberTooBig { .. })));
+        assert!(matches!(run("<<", int(1), big("18446744073709551615")), Err(RuntimeError::NumberTooBig { .. })));
+    }
+
+    #[test]
+    fn bitwise_on_bools_gives_bools() {
+        assert_eq!(run("^", Value::Bool(1), Value::Bool(1)).unwrap(), "Bool(0)");
+        assert_eq!(run("&", Value::Bool(1), int(3)).unwrap(), "int 1");
+    }
+}
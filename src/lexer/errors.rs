@@ -0,0 +1,48 @@
+use std::fmt;
+
+use crate::utils::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub enum LexError {
+    UnexpectedCharacter { character: char, span: Span },
+    UnterminatedComment { span: Span },
+    UnterminatedInterpolation { span: Span },
+    UnterminatedString { span: Span },
+    InvalidEscape { sequence: String, span: Span },
+}
+
+impl LexError {
+    pub fn span(&self) -> Span {
+        match self {
+            LexError::UnexpectedCharacter { span, .. }
+            | LexError::UnterminatedComment { span }
+            | LexError::UnterminatedInterpolation { span }
+            | LexError::UnterminatedString { span }
+            | LexError::InvalidEscape { span, .. } => *span,
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexError::UnexpectedCharacter { character, .. } => {
+                write!(f, "Unexpected character: {}", character)
+            }
+            LexError::UnterminatedComment { .. } => {
+                write!(f, "This block comment is never closed, add a matching ']#'")
+            }
+            LexError::UnterminatedInterpolation { .. } => {
+                write!(f, "This '{{' in a string is never closed, add a matching '}}' (or write '{{{{' for a brace)")
+            }
+            LexError::UnterminatedString { .. } => {
+                write!(f, "This string is never closed, add the matching quotes")
+            }
+            LexError::InvalidEscape { sequence, .. } => write!(
+                f,
+                "Invalid escape sequence {}, use \\n, \\t, \\r, \\0, \\\\, \\\", \\{{, \\}} or \\u{{...}}",
+                sequence
+            ),
+        }
+    }
+}
@@ -1,2 +1,3 @@
+pub mod errors;
 pub mod tokens;
-pub mod tokenizer;
\ No newline at end of file
+pub mod tokenizer;
@@ -1,13 +1,18 @@
+use crate::lexer::errors::LexError;
 use crate::lexer::tokens::{Token, TokenType, create_token, keywords};
+use crate::utils::diagnostics::Span;
 
-pub fn tokenize(input: &str) -> Vec<Token> {
+pub fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
     let mut tokens = Vec::new();
     let chars: Vec<char> = input.chars().collect();
+    let line_starts = line_starts(&chars);
     let mut current = 0;
     let keywords_map = keywords();
+    let mut interpolations: Vec<Interpolation> = Vec::new();
 
     while current < chars.len() {
         let ch = chars[current];
+        let start = current;
 
         // === Whitespace ===
         if ch.is_whitespace() {
@@ -15,45 +20,186 @@ pub fn tokenize(input: &str) -> Vec<Token> {
             continue;
         }
 
+        // === Comments ===
+        if ch == '#' {
+            match chars.get(current + 1) {
+                // Block comment: #[ ... ]#, can be nested
+                Some('[') => {
+                    let mut depth = 0;
+                    while current < chars.len() {
+                        if chars[current] == '#' && chars.get(current + 1) == Some(&'[') {
+                            depth += 1;
+                            current += 2;
+                        } else if chars[current] == ']' && chars.get(current + 1) == Some(&'#') {
+                            depth -= 1;
+                            current += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        } else {
+                            current += 1;
+                        }
+                    }
+
+                    if depth > 0 {
+                        let span = span_between(&line_starts, start, start + 2);
+                        return Err(LexError::UnterminatedComment { span });
+                    }
+                }
+                // Doc comment: ## ..., attached to the next declaration by the parser
+                Some('#') => {
+                    current += 2;
+                    let mut value = String::new();
+                    while current < chars.len() && chars[current] != '\n' {
+                        value.push(chars[current]);
+                        current += 1;
+                    }
+
+                    let span = span_between(&line_starts, start, current);
+                    let text = value.strip_prefix(' ').unwrap_or(&value).trim_end();
+                    tokens.push(create_token(TokenType::DocComment, text, span));
+                }
+                // Line comment: # ...
+                _ => {
+                    while current < chars.len() && chars[current] != '\n' {
+                        current += 1;
+                    }
+                }
+            }
+            continue;
+        }
+
         // === String Literals ===
-        if ch == '"' {
+        // A string goes on after the '}' closing one of its expressions
+        let string = if ch == '}' && interpolations.last().is_some_and(|i| i.depth == 0) {
+            let span = span_between(&line_starts, start, start + 1);
+            tokens.push(create_token(TokenType::InterpolationEnd, "}", span));
             current += 1;
-            let mut value = String::new();
-
-            while current < chars.len() && chars[current] != '"' {
-                value.push(chars[current]);
-                current += 1;
+            interpolations.pop().map(|interpolation| interpolation.string)
+        } else {
+            let string = string_start(&chars, current);
+            if let Some(string) = string {
+                current += string.length;
             }
+            string
+        };
+
+        if let Some(string) = string {
+            // Running out of file inside a `{expression}` is most likely a missing '}', not a missing quote
+            let (value, end, interpolated) = read_string(&chars, current, string, &line_starts).map_err(|error| {
+                match (error, interpolations.first()) {
+                    (LexError::UnterminatedString { .. }, Some(outermost)) => LexError::UnterminatedInterpolation {
+                        span: span_between(&line_starts, outermost.open, outermost.open + 1),
+                    },
+                    (error, _) => error,
+                }
+            })?;
+            let span = span_between(&line_starts, start, end);
+            current = end;
 
-            tokens.push(create_token(TokenType::String, &value));
-            current += 1; // Skip closing quote
+            if interpolated {
+                interpolations.push(Interpolation {
+                    open: end - 1,
+                    depth: 0,
+                    string,
+                });
+                tokens.push(create_token(TokenType::StringPart, &value, span));
+            } else {
+                tokens.push(create_token(TokenType::String, &value, span));
+            }
             continue;
         }
 
+        // === Three-Character Operators ===
+        if let Some(triple) = chars.get(current..current + 3) {
+            let triple: String = triple.iter().collect();
+            if matches!(triple.as_str(), "**=" | "//=" | "<<=" | ">>=") {
+                let span = span_between(&line_starts, start, start + 3);
+                tokens.push(create_token(TokenType::CompoundAssign, &triple, span));
+                current += 3;
+                continue;
+            }
+        }
+
+        // === Two-Character Operators ===
+        if let Some(&next) = chars.get(current + 1) {
+            let pair: String = [ch, next].iter().collect();
+            if pair == ".." {
+                let span = span_between(&line_starts, start, start + 2);
+                tokens.push(create_token(TokenType::Range, &pair, span));
+                current += 2;
+                continue;
+            }
+
+            if pair == "=>" {
+                let span = span_between(&line_starts, start, start + 2);
+                tokens.push(create_token(TokenType::Arrow, &pair, span));
+                current += 2;
+                continue;
+            }
+
+            if matches!(pair.as_str(), "+=" | "-=" | "*=" | "/=" | "%=" | "&=" | "|=" | "^=") {
+                let span = span_between(&line_starts, start, start + 2);
+                tokens.push(create_token(TokenType::CompoundAssign, &pair, span));
+                current += 2;
+                continue;
+            }
+            if matches!(pair.as_str(), "==" | "!=" | "<=" | ">=" | "&&" | "||" | "**" | "//" | "<<" | ">>") {
+                let span = span_between(&line_starts, start, start + 2);
+                tokens.push(create_token(TokenType::Operator, &pair, span));
+                current += 2;
+                continue;
+            }
+        }
+
         // === Single-Character Tokens ===
+        let span = span_between(&line_starts, start, start + 1);
         match ch {
             '(' | ')' => {
-                tokens.push(create_token(TokenType::Parenthesis, &ch.to_string()));
+                tokens.push(create_token(TokenType::Parenthesis, &ch.to_string(), span));
                 current += 1;
                 continue;
             }
             '{' | '}' => {
-                tokens.push(create_token(TokenType::Brace, &ch.to_string()));
+                if let Some(interpolation) = interpolations.last_mut() {
+                    // The '}' closing the expression itself was handled with the strings
+                    if ch == '{' {
+                        interpolation.depth += 1;
+                    } else {
+                        interpolation.depth -= 1;
+                    }
+                }
+                tokens.push(create_token(TokenType::Brace, &ch.to_string(), span));
+                current += 1;
+                continue;
+            }
+            '[' | ']' => {
+                tokens.push(create_token(TokenType::Bracket, &ch.to_string(), span));
                 current += 1;
                 continue;
             }
             ',' => {
-                tokens.push(create_token(TokenType::Comma, &ch.to_string()));
+                tokens.push(create_token(TokenType::Comma, &ch.to_string(), span));
+                current += 1;
+                continue;
+            }
+            '.' => {
+                tokens.push(create_token(TokenType::Dot, ".", span));
+                current += 1;
+                continue;
+            }
+            ':' => {
+                tokens.push(create_token(TokenType::Colon, ":", span));
                 current += 1;
                 continue;
             }
             '=' => {
-                tokens.push(create_token(TokenType::Equal, "="));
+                tokens.push(create_token(TokenType::Equal, "=", span));
                 current += 1;
                 continue;
             }
-            '+' | '-' | '*' | '/' => {
-                tokens.push(create_token(TokenType::Operator, &ch.to_string()));
+            '+' | '-' | '*' | '/' | '%' | '<' | '>' | '!' | '&' | '|' | '^' | '~' => {
+                tokens.push(create_token(TokenType::Operator, &ch.to_string(), span));
                 current += 1;
                 continue;
             }
@@ -61,17 +207,28 @@ pub fn tokenize(input: &str) -> Vec<Token> {
         }
 
         // === Numbers ===
+        // Everything that could be part of the literal is taken, the parser checks that it makes sense
         if ch.is_ascii_digit() {
             let mut num_str = String::new();
+            let radix_prefix = ch == '0' && matches!(chars.get(current + 1), Some('x' | 'X' | 'b' | 'B' | 'o' | 'O'));
 
-            while current < chars.len()
-                && (chars[current].is_ascii_digit() || chars[current] == '.')
-            {
-                num_str.push(chars[current]);
+            while let Some(&c) = chars.get(current) {
+                let digit_follows = chars.get(current + 1).is_some_and(|next| next.is_ascii_digit());
+                let part_of_number = c.is_ascii_alphanumeric()
+                    || c == '_'
+                    // A dot only belongs to the number when a digit follows, so `0..10` stays a range
+                    || (c == '.' && digit_follows)
+                    // The sign of an exponent, as in `1e-3`
+                    || (matches!(c, '+' | '-') && !radix_prefix && digit_follows && matches!(chars[current - 1], 'e' | 'E'));
+                if !part_of_number {
+                    break;
+                }
+                num_str.push(c);
                 current += 1;
             }
 
-            tokens.push(create_token(TokenType::Number, &num_str));
+            let span = span_between(&line_starts, start, current);
+            tokens.push(create_token(TokenType::Number, &num_str, span));
             continue;
         }
 
@@ -86,9 +243,10 @@ pub fn tokenize(input: &str) -> Vec<Token> {
                 current += 1;
             }
 
+            let span = span_between(&line_starts, start, current);
             match value.as_str() {
                 "twue" | "fawse" => {
-                    tokens.push(create_token(TokenType::Bool, &value));
+                    tokens.push(create_token(TokenType::Bool, &value, span));
                     continue;
                 }
                 _ => {
@@ -97,15 +255,181 @@ pub fn tokenize(input: &str) -> Vec<Token> {
                         .cloned()
                         .unwrap_or(TokenType::Identifier);
         
-                    tokens.push(create_token(token_type, &value));
+                    tokens.push(create_token(token_type, &value, span));
                     continue;
                 }
             }
         }
 
-        panic!("Unexpected character: {}", ch);
+        return Err(LexError::UnexpectedCharacter { character: ch, span });
+    }
+
+    if let Some(interpolation) = interpolations.first() {
+        let span = span_between(&line_starts, interpolation.open, interpolation.open + 1);
+        return Err(LexError::UnterminatedInterpolation { span });
     }
 
     // println!("TOKENS {:?}", tokens);
-    tokens
+    Ok(tokens)
+}
+
+// === Strings ===
+
+/// Where a string literal begins and how it was opened, which tells how it ends.
+#[derive(Debug, Clone, Copy)]
+struct StringStart {
+    position: usize,
+    /// Length of the opening: `"`, `"""`, `r"` or `r"""`.
+    length: usize,
+    /// Triple-quoted strings end at the next `"""`, so a lone `"` can be used inside them.
+    triple: bool,
+    /// Raw strings keep backslashes and braces as they are.
+    raw: bool,
+}
+
+/// A `{expression}` inside a string, which the lexer is in the middle of.
+struct Interpolation {
+    /// Position of the '{'.
+    open: usize,
+    /// Number of braces (of maps) opened inside the expression and not closed yet.
+    depth: usize,
+    /// The string it belongs to, which goes on after the closing '}'.
+    string: StringStart,
+}
+
+fn string_start(chars: &[char], position: usize) -> Option<StringStart> {
+    let raw = chars[position] == 'r';
+    let quote = position + raw as usize;
+    if chars.get(quote) != Some(&'"') {
+        return None;
+    }
+
+    let triple = chars.get(quote + 1) == Some(&'"') && chars.get(quote + 2) == Some(&'"');
+    Some(StringStart {
+        position,
+        length: raw as usize + if triple { 3 } else { 1 },
+        triple,
+        raw,
+    })
+}
+
+/// Reads the text of a string from `current` up to its closing quotes, or up to the '{' starting
+/// one of its expressions. Returns the text, the position right after what stopped it, and
+/// whether that was a '{'.
+fn read_string(
+    chars: &[char],
+    mut current: usize,
+    string: StringStart,
+    line_starts: &[usize],
+) -> Result<(String, usize, bool), LexError> {
+    let mut value = String::new();
+
+    // A line break right after the opening `"""` is only there for the layout
+    if string.triple && current == string.position + string.length {
+        if chars.get(current) == Some(&'\r') {
+            current += 1;
+        }
+        if chars.get(current) == Some(&'\n') {
+            current += 1;
+        }
+    }
+
+    loop {
+        let Some(&ch) = chars.get(current) else {
+            let span = span_between(line_starts, string.position, string.position + string.length);
+            return Err(LexError::UnterminatedString { span });
+        };
+
+        let closing = if string.triple { 3 } else { 1 };
+        if (0..closing).all(|i| chars.get(current + i) == Some(&'"')) {
+            return Ok((value, current + closing, false));
+        }
+
+        if string.raw {
+            value.push(ch);
+            current += 1;
+            continue;
+        }
+
+        // `{{` and `}}` stand for the braces themselves, a single '{' starts an expression
+        match (ch, chars.get(current + 1)) {
+            ('\\', _) => {
+                let (escaped, length) = read_escape(chars, current, line_starts)?;
+                value.push(escaped);
+                current += length;
+            }
+            ('{', Some('{')) | ('}', Some('}')) => {
+                value.push(ch);
+                current += 2;
+            }
+            ('{', _) => return Ok((value, current + 1, true)),
+            _ => {
+                value.push(ch);
+                current += 1;
+            }
+        }
+    }
+}
+
+/// Reads the escape sequence whose backslash is at `start`: `\n`, `\t`, `\r`, `\0`, `\\`, `\"`,
+/// `\{`, `\}` or `\u{1F431}`. Returns the character it stands for and the length of the sequence.
+fn read_escape(chars: &[char], start: usize, line_starts: &[usize]) -> Result<(char, usize), LexError> {
+    let invalid = |end: usize| {
+        let end = end.min(chars.len());
+        LexError::InvalidEscape {
+            sequence: chars[start..end].iter().collect(),
+            span: span_between(line_starts, start, end),
+        }
+    };
+
+    let escaped = match chars.get(start + 1) {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('0') => '\0',
+        Some('\\') => '\\',
+        Some('"') => '"',
+        Some('{') => '{',
+        Some('}') => '}',
+        Some('u') => {
+            if chars.get(start + 2) != Some(&'{') {
+                return Err(invalid(start + 2));
+            }
+            let digits = start + 3;
+            let close = (digits..chars.len())
+                .find(|&i| !chars[i].is_ascii_hexdigit())
+                .unwrap_or(chars.len());
+            if chars.get(close) != Some(&'}') || close == digits || close - digits > 6 {
+                return Err(invalid(close + 1));
+            }
+
+            let hex: String = chars[digits..close].iter().collect();
+            let escaped = u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| invalid(close + 1))?;
+            return Ok((escaped, close + 1 - start));
+        }
+        _ => return Err(invalid(start + 2)),
+    };
+    Ok((escaped, 2))
+}
+
+// === Source Positions ===
+
+/// Index of the first character of every line.
+fn line_starts(chars: &[char]) -> Vec<usize> {
+    let mut starts = vec![0];
+    for (index, ch) in chars.iter().enumerate() {
+        if *ch == '\n' {
+            starts.push(index + 1);
+        }
+    }
+    starts
+}
+
+fn span_between(line_starts: &[usize], start: usize, end: usize) -> Span {
+    let line = line_starts.partition_point(|&line_start| line_start <= start);
+    let column = start - line_starts[line - 1] + 1;
+    Span::new(line, column, end.saturating_sub(start))
 }
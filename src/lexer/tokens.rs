@@ -1,34 +1,66 @@
 use std::collections::HashMap;
 
+use crate::utils::diagnostics::Span;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TokenType {
     OwO,
     Print,
     FunctionDef,
     VarDecl,
+    ConstDecl,
     Identifier,
     Number,
     String,
     Bool,
+    Nil,
     Operator,
     Parenthesis,
     Brace,
+    Bracket,
     Comma,
+    Colon,
+    Dot,
     Equal,
     Return,
     KindOf,
+    DocComment,
+    If,
+    ElseIf,
+    Else,
+    While,
+    Break,
+    Continue,
+    For,
+    In,
+    Step,
+    Range,
+    CompoundAssign,
+    Record,
+    Class,
+    SelfRef,
+    Super,
+    Enum,
+    Match,
+    Arrow,
+    /// Text of a string up to one of its `{expression}`s, the expression's tokens come next.
+    StringPart,
+    /// The '}' closing an expression inside a string, the rest of the string comes next.
+    InterpolationEnd,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub token_type: TokenType,
     pub value: String,
+    pub span: Span,
 }
 
-pub fn create_token(token_type: TokenType, value: &str) -> Token {
+pub fn create_token(token_type: TokenType, value: &str, span: Span) -> Token {
     Token {
         token_type,
         value: value.to_string(),
+        span,
     }
 }
 
@@ -38,8 +70,28 @@ pub fn keywords() -> HashMap<&'static str, TokenType> {
     map.insert("meow", TokenType::Print);
     map.insert("sparkle", TokenType::FunctionDef);
     map.insert("nyan", TokenType::VarDecl);
+    map.insert("fowever", TokenType::ConstDecl);
     map.insert("bringback", TokenType::Return);
     map.insert("bb", TokenType::Return); // Alias for bringback
     map.insert("kindOf", TokenType::KindOf);
+    map.insert("nani", TokenType::If);
+    map.insert("ownani", TokenType::ElseIf);
+    map.insert("otherwise", TokenType::Else);
+    map.insert("whiwe", TokenType::While);
+    map.insert("bweak", TokenType::Break);
+    map.insert("continyu", TokenType::Continue);
+    map.insert("fow", TokenType::For);
+    map.insert("in", TokenType::In);
+    map.insert("by", TokenType::Step);
+    map.insert("bento", TokenType::Record);
+    map.insert("kwass", TokenType::Class);
+    map.insert("sewf", TokenType::SelfRef);
+    map.insert("supew", TokenType::Super);
+    map.insert("flavor", TokenType::Enum);
+    map.insert("meowtch", TokenType::Match);
+    map.insert("nuww", TokenType::Nil);
+    map.insert("and", TokenType::Operator); // Same as &&
+    map.insert("or", TokenType::Operator); // Same as ||
+    map.insert("not", TokenType::Operator); // Same as !
     map
 }
@@ -1,5 +1,7 @@
 use std::env;
 use std::fs;
+use std::process;
+use std::thread;
 use std::time::Instant;
 
 mod interpreter;
@@ -10,21 +12,75 @@ mod utils;
 use interpreter::interpreter::run;
 use lexer::tokenizer::tokenize;
 use parser::parser::parse;
+use parser::errors::ParseError;
+use parser::resolver::resolve;
+use utils::diagnostics::{Diagnostic, render};
+
+// === Exit Codes ===
+
+const EXIT_USAGE: i32 = 1;
+const EXIT_IO: i32 = 2;
+const EXIT_LEX: i32 = 3;
+const EXIT_PARSE: i32 = 4;
+const EXIT_RUNTIME: i32 = 5;
+
+/// Stack for the thread running the script. Every `sparkle` call nests a few interpreter calls,
+/// the main thread's stack runs out long before the interpreter's own recursion limit.
+const STACK_SIZE: usize = 512 * 1024 * 1024;
 
 fn main() {
+    let script = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run_script)
+        .unwrap_or_else(|error| {
+            eprintln!("Failed to start the interpreter: {}", error);
+            process::exit(EXIT_USAGE);
+        });
+    if let Err(panic) = script.join() {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+fn run_script() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
         eprintln!("Pwease give me a file to run! 🥺👉👈");
-        std::process::exit(1);
+        process::exit(EXIT_USAGE);
     }
 
     let start_time = Instant::now();
 
     let file_name = &args[1];
-    let code = fs::read_to_string(file_name).expect("Failed to read the file");
-    let tokens = tokenize(&code);
-    let ast = parse(&tokens);
-    run(&ast);
+    let code = fs::read_to_string(file_name).unwrap_or_else(|error| {
+        eprintln!("Failed to read the file \"{}\": {}", file_name, error);
+        process::exit(EXIT_IO);
+    });
+
+    let report = |diagnostic: Diagnostic, exit_code: i32| -> ! {
+        eprintln!("{}", render(&diagnostic, file_name, &code));
+        process::exit(exit_code)
+    };
+
+    let tokens = tokenize(&code).unwrap_or_else(|error| {
+        report(Diagnostic::new("syntax error", error.to_string(), error.span()), EXIT_LEX)
+    });
+    let report_all = |errors: &[ParseError], label: &'static str| -> ! {
+        for error in errors {
+            let diagnostic = Diagnostic::new(label, error.to_string(), error.span());
+            eprintln!("{}\n", render(&diagnostic, file_name, &code));
+        }
+        eprintln!("Found {} {}(s) 😿", errors.len(), label);
+        process::exit(EXIT_PARSE)
+    };
+
+    let ast = parse(&tokens).unwrap_or_else(|failure| report_all(&failure.errors, "syntax error"));
+    let errors = resolve(&ast);
+    if !errors.is_empty() {
+        report_all(&errors, "error");
+    }
+    if let Err(error) = run(&ast) {
+        report(Diagnostic::new("runtime error", error.to_string(), error.span()), EXIT_RUNTIME);
+    }
 
     let duration = start_time.elapsed();
     println!("------------------------------------\nScript ran in: {:.3?} OwO", duration);
@@ -1,77 +1,251 @@
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+
+use crate::utils::decimal::Decimal;
+use crate::utils::diagnostics::Span;
+
 #[derive(Debug, Clone)]
 pub enum ASTNode {
     OwO(OwONode),
     Print(PrintNode),
     FunctionDeclaration(FunctionDeclarationNode),
+    RecordDeclaration(RecordDeclarationNode),
+    ClassDeclaration(ClassDeclarationNode),
+    EnumDeclaration(EnumDeclarationNode),
     FunctionCall(FunctionCallNode),
+    Lambda(LambdaNode),
     VariableDeclaration(VariableDeclarationNode),
     VariableReference(VariableReferenceNode),
+    Assignment(AssignmentNode),
     StringLiteral(StringLiteralNode),
+    Interpolation(InterpolationNode),
     NumberLiteral(NumberLiteralNode),
     BoolLiteral(BoolLiteralNode),
+    NilLiteral(NilLiteralNode),
     BinaryExpression(BinaryExpressionNode),
+    LogicalExpression(LogicalExpressionNode),
+    UnaryExpression(UnaryExpressionNode),
     Return(ReturnNode),
     KindOf(KindOfNode),
+    If(IfNode),
+    While(WhileNode),
+    Break(BreakNode),
+    Continue(ContinueNode),
+    For(ForNode),
+    Range(RangeNode),
+    List(ListNode),
+    Map(MapNode),
+    Index(IndexNode),
+    MemberAccess(MemberAccessNode),
+    Super(SuperNode),
+    Match(MatchNode),
+    Slice(SliceNode),
+    Error(ErrorNode),
     None,
 }
 
+impl ASTNode {
+    pub fn span(&self) -> Span {
+        match self {
+            ASTNode::OwO(node) => node.span,
+            ASTNode::Print(node) => node.span,
+            ASTNode::FunctionDeclaration(node) => node.span,
+            ASTNode::RecordDeclaration(node) => node.span,
+            ASTNode::ClassDeclaration(node) => node.span,
+            ASTNode::EnumDeclaration(node) => node.span,
+            ASTNode::FunctionCall(node) => node.span,
+            ASTNode::Lambda(node) => node.span,
+            ASTNode::VariableDeclaration(node) => node.span,
+            ASTNode::VariableReference(node) => node.span,
+            ASTNode::Assignment(node) => node.span,
+            ASTNode::StringLiteral(node) => node.span,
+            ASTNode::Interpolation(node) => node.span,
+            ASTNode::NumberLiteral(node) => node.span,
+            ASTNode::BoolLiteral(node) => node.span,
+            ASTNode::NilLiteral(node) => node.span,
+            ASTNode::BinaryExpression(node) => node.span,
+            ASTNode::LogicalExpression(node) => node.span,
+            ASTNode::UnaryExpression(node) => node.span,
+            ASTNode::Return(node) => node.span,
+            ASTNode::KindOf(node) => node.span,
+            ASTNode::If(node) => node.span,
+            ASTNode::While(node) => node.span,
+            ASTNode::Break(node) => node.span,
+            ASTNode::Continue(node) => node.span,
+            ASTNode::For(node) => node.span,
+            ASTNode::Range(node) => node.span,
+            ASTNode::List(node) => node.span,
+            ASTNode::Map(node) => node.span,
+            ASTNode::Index(node) => node.span,
+            ASTNode::MemberAccess(node) => node.span,
+            ASTNode::Super(node) => node.span,
+            ASTNode::Match(node) => node.span,
+            ASTNode::Slice(node) => node.span,
+            ASTNode::Error(node) => node.span,
+            ASTNode::None => Span::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PrintNode {
     pub expression: Box<ASTNode>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct OwONode {
     pub expression: Box<ASTNode>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionCallNode {
-    pub name: String,
+    pub callee: Box<ASTNode>,
     pub arguments: Vec<ASTNode>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionDeclarationNode {
     pub name: String,
     pub params: Vec<ASTNode>,
-    pub body: Vec<ASTNode>,
+    pub body: Rc<[ASTNode]>, // Shared with the function values created from it
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+/// Anonymous `sparkle(params) { ... }` expression.
+#[derive(Debug, Clone)]
+pub struct LambdaNode {
+    pub params: Vec<ASTNode>,
+    pub body: Rc<[ASTNode]>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct VariableDeclarationNode {
     pub name: String,
     pub value: Box<ASTNode>,
+    /// Declared with `fowever`: can't be reassigned or declared again in the same scope.
+    pub constant: bool,
+    pub doc: Option<String>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct VariableReferenceNode {
     pub name: String,
+    pub span: Span,
+}
+
+/// `target = value`, or a compound form like `target += value` (then `operator` is `"+"`).
+#[derive(Debug, Clone)]
+pub struct AssignmentNode {
+    pub target: Box<ASTNode>,
+    pub operator: Option<String>,
+    pub value: Box<ASTNode>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct StringLiteralNode {
     pub value: String,
+    pub span: Span,
+}
+
+/// `"hewwo {name}!"`: the pieces of text and the expressions between them, in order.
+#[derive(Debug, Clone)]
+pub struct InterpolationNode {
+    pub parts: Vec<ASTNode>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct NumberLiteralNode {
-    pub value: f64,
+    pub value: Numeric,
+    pub span: Span,
+}
+
+/// The value of a number literal: `42` and `0xFF` are ints, `3.14` and `1e3` are floats.
+/// Ints too big for 64 bits are kept whole as a `BigInt`.
+#[derive(Debug, Clone)]
+pub enum Numeric {
+    Int(i64),
+    BigInt(BigInt),
+    Float(f64),
+    /// A literal ending in `d`, like `0.1d`.
+    Decimal(Decimal),
 }
 
 #[derive(Debug, Clone)]
 pub struct BoolLiteralNode {
     pub value: i8,
+    pub span: Span,
+}
+
+/// `nuww`, the value meaning "nothing here".
+#[derive(Debug, Clone)]
+pub struct NilLiteralNode {
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct ReturnNode {
-    pub value: Box<ASTNode>,
+    /// `None` for a bare `bringback`, which gives back `nuww`.
+    pub value: Option<Box<ASTNode>>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct KindOfNode {
     pub expression: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// `nani` statement. An `ownani` branch is stored as a nested `IfNode` in `else_body`.
+#[derive(Debug, Clone)]
+pub struct IfNode {
+    pub condition: Box<ASTNode>,
+    pub body: Vec<ASTNode>,
+    pub else_body: Option<Vec<ASTNode>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct WhileNode {
+    pub condition: Box<ASTNode>,
+    pub body: Vec<ASTNode>,
+    pub span: Span,
+}
+
+/// `fow variable in iterable { ... }`, where the iterable is a range or a string.
+#[derive(Debug, Clone)]
+pub struct ForNode {
+    pub variable: String,
+    pub iterable: Box<ASTNode>,
+    pub body: Vec<ASTNode>,
+    pub span: Span,
+}
+
+/// `start..end` or `start..end by step`, the end is excluded.
+#[derive(Debug, Clone)]
+pub struct RangeNode {
+    pub start: Box<ASTNode>,
+    pub end: Box<ASTNode>,
+    pub step: Option<Box<ASTNode>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct BreakNode {
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContinueNode {
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -79,4 +253,164 @@ pub struct BinaryExpressionNode {
     pub operator: String,
     pub left: Box<ASTNode>,
     pub right: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// `&&` and `||`, kept apart from `BinaryExpressionNode` because the right side may never run.
+#[derive(Debug, Clone)]
+pub struct LogicalExpressionNode {
+    pub operator: String,
+    pub left: Box<ASTNode>,
+    pub right: Box<ASTNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnaryExpressionNode {
+    pub operator: String,
+    pub operand: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// `[a, b, c]`
+#[derive(Debug, Clone)]
+pub struct ListNode {
+    pub elements: Vec<ASTNode>,
+    pub span: Span,
+}
+
+/// `{ key: value, ... }`, entries keep the order they are written in.
+#[derive(Debug, Clone)]
+pub struct MapNode {
+    pub entries: Vec<(ASTNode, ASTNode)>,
+    pub span: Span,
+}
+
+/// `target[index]` on a list or a string, or `target[key]` on a map. Also used as an assignment target.
+#[derive(Debug, Clone)]
+pub struct IndexNode {
+    pub target: Box<ASTNode>,
+    pub index: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// `bento Name { field, ... }`, a record type built by calling it like a function: `Name(a, b)`.
+#[derive(Debug, Clone)]
+pub struct RecordDeclarationNode {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+/// `kwass Name < Parent { sparkle init(...) { ... } ... }`, the parent being optional.
+/// Inside methods, `sewf` is parsed as a `VariableReference` the interpreter fills in.
+#[derive(Debug, Clone)]
+pub struct ClassDeclarationNode {
+    pub name: String,
+    pub superclass: Option<Box<ASTNode>>,
+    pub methods: Vec<FunctionDeclarationNode>,
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+/// `flavor Name { Variant, Variant(field, ...), ... }`, variants are reached with `Name.Variant`.
+#[derive(Debug, Clone)]
+pub struct EnumDeclarationNode {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+/// `meowtch subject { pattern nani guard => body, ... }`. The first arm that matches runs.
+#[derive(Debug, Clone)]
+pub struct MatchNode {
+    pub subject: Box<ASTNode>,
+    pub arms: Vec<MatchArm>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<ASTNode>,
+    pub body: MatchBody,
+}
+
+/// A `meowtch` used as a statement has blocks (or single statements) as arm bodies,
+/// one used as a value has expressions.
+#[derive(Debug, Clone)]
+pub enum MatchBody {
+    Block(Vec<ASTNode>),
+    Expression(Box<ASTNode>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `_`, matches anything.
+    Wildcard,
+    /// A name, matches anything and gives it that name inside the arm.
+    Binding(String),
+    /// A number, string or bool literal, matches an equal value.
+    Literal(Box<ASTNode>),
+    /// `Enum.Variant`, or `Enum.Variant(patterns)` to also match what the variant carries.
+    Variant {
+        enum_name: String,
+        variant: String,
+        fields: Option<Vec<Pattern>>,
+        span: Span,
+    },
+}
+
+impl Pattern {
+    /// Names the pattern gives to parts of the value, in order.
+    pub fn bindings(&self) -> Vec<&str> {
+        match self {
+            Pattern::Binding(name) => vec![name.as_str()],
+            Pattern::Variant { fields: Some(fields), .. } => fields.iter().flat_map(Pattern::bindings).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether the pattern matches every value.
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard | Pattern::Binding(_))
+    }
+}
+
+/// `supew.method`, the parent class's version of a method, bound to `sewf`.
+#[derive(Debug, Clone)]
+pub struct SuperNode {
+    pub method: String,
+    pub span: Span,
+}
+
+/// `target.member`, also used as an assignment target.
+#[derive(Debug, Clone)]
+pub struct MemberAccessNode {
+    pub target: Box<ASTNode>,
+    pub member: String,
+    pub span: Span,
+}
+
+/// `target[start..end]`, either bound can be left out.
+#[derive(Debug, Clone)]
+pub struct SliceNode {
+    pub target: Box<ASTNode>,
+    pub start: Option<Box<ASTNode>>,
+    pub end: Option<Box<ASTNode>>,
+    pub span: Span,
+}
+
+/// Stands in for a statement that failed to parse, so the rest of the tree stays usable.
+#[derive(Debug, Clone)]
+pub struct ErrorNode {
+    pub span: Span,
 }
@@ -1,17 +1,20 @@
 use crate::parser::ast::*;
+use crate::utils::diagnostics::Span;
 
 pub struct ASTBuilder;
 
 impl ASTBuilder {
-    pub fn create_print_node(expression: ASTNode) -> ASTNode {
+    pub fn create_print_node(expression: ASTNode, span: Span) -> ASTNode {
         ASTNode::Print(PrintNode {
             expression: Box::new(expression),
+            span,
         })
     }
 
-    pub fn create_owo_node(expression: ASTNode) -> ASTNode {
+    pub fn create_owo_node(expression: ASTNode, span: Span) -> ASTNode {
         ASTNode::OwO(OwONode {
             expression: Box::new(expression),
+            span,
         })
     }
 
@@ -19,14 +22,78 @@ impl ASTBuilder {
         name: String,
         params: Vec<ASTNode>,
         body: Vec<ASTNode>,
+        span: Span,
     ) -> ASTNode {
-        ASTNode::FunctionDeclaration(FunctionDeclarationNode { name, params, body })
+        ASTNode::FunctionDeclaration(FunctionDeclarationNode {
+            name,
+            params,
+            body: body.into(),
+            doc: None,
+            span,
+        })
+    }
+
+    pub fn create_record_declaration_node(name: String, fields: Vec<String>, span: Span) -> ASTNode {
+        ASTNode::RecordDeclaration(RecordDeclarationNode {
+            name,
+            fields,
+            doc: None,
+            span,
+        })
+    }
+
+    pub fn create_class_declaration_node(
+        name: String,
+        superclass: Option<ASTNode>,
+        methods: Vec<FunctionDeclarationNode>,
+        span: Span,
+    ) -> ASTNode {
+        ASTNode::ClassDeclaration(ClassDeclarationNode {
+            name,
+            superclass: superclass.map(Box::new),
+            methods,
+            doc: None,
+            span,
+        })
     }
 
-    pub fn create_variable_declaration_node(name: String, value: ASTNode) -> ASTNode {
+    pub fn create_enum_declaration_node(name: String, variants: Vec<EnumVariant>, span: Span) -> ASTNode {
+        ASTNode::EnumDeclaration(EnumDeclarationNode {
+            name,
+            variants,
+            doc: None,
+            span,
+        })
+    }
+
+    pub fn create_match_node(subject: ASTNode, arms: Vec<MatchArm>, span: Span) -> ASTNode {
+        ASTNode::Match(MatchNode {
+            subject: Box::new(subject),
+            arms,
+            span,
+        })
+    }
+
+    pub fn create_lambda_node(params: Vec<ASTNode>, body: Vec<ASTNode>, span: Span) -> ASTNode {
+        ASTNode::Lambda(LambdaNode {
+            params,
+            body: body.into(),
+            span,
+        })
+    }
+
+    pub fn create_variable_declaration_node(
+        name: String,
+        value: ASTNode,
+        constant: bool,
+        span: Span,
+    ) -> ASTNode {
         ASTNode::VariableDeclaration(VariableDeclarationNode {
             name,
             value: Box::new(value),
+            constant,
+            doc: None,
+            span,
         })
     }
 
@@ -35,42 +102,198 @@ impl ASTBuilder {
         left: ASTNode,
         right: ASTNode,
     ) -> ASTNode {
+        let span = left.span().to(right.span());
         ASTNode::BinaryExpression(BinaryExpressionNode {
             operator,
             left: Box::new(left),
             right: Box::new(right),
+            span,
+        })
+    }
+
+    pub fn create_logical_expression_node(
+        operator: String,
+        left: ASTNode,
+        right: ASTNode,
+    ) -> ASTNode {
+        let span = left.span().to(right.span());
+        ASTNode::LogicalExpression(LogicalExpressionNode {
+            operator,
+            left: Box::new(left),
+            right: Box::new(right),
+            span,
         })
     }
 
-    pub fn create_variable_reference_node(name: String) -> ASTNode {
-        ASTNode::VariableReference(VariableReferenceNode { name })
+    pub fn create_unary_expression_node(operator: String, operand: ASTNode, span: Span) -> ASTNode {
+        ASTNode::UnaryExpression(UnaryExpressionNode {
+            operator,
+            operand: Box::new(operand),
+            span,
+        })
     }
 
-    pub fn create_string_literal_node(value: String) -> ASTNode {
-        ASTNode::StringLiteral(StringLiteralNode { value })
+    pub fn create_variable_reference_node(name: String, span: Span) -> ASTNode {
+        ASTNode::VariableReference(VariableReferenceNode { name, span })
     }
 
-    pub fn create_number_literal_node(value: f64) -> ASTNode {
-        ASTNode::NumberLiteral(NumberLiteralNode { value })
+    pub fn create_assignment_node(
+        target: ASTNode,
+        operator: Option<String>,
+        value: ASTNode,
+        span: Span,
+    ) -> ASTNode {
+        ASTNode::Assignment(AssignmentNode {
+            target: Box::new(target),
+            operator,
+            value: Box::new(value),
+            span,
+        })
     }
 
-    pub fn create_bool_literal_node(value: i8) -> ASTNode {
-        ASTNode::BoolLiteral(BoolLiteralNode { value })
+    pub fn create_string_literal_node(value: String, span: Span) -> ASTNode {
+        ASTNode::StringLiteral(StringLiteralNode { value, span })
     }
 
-    pub fn create_function_call_node(name: String, arguments: Vec<ASTNode>) -> ASTNode {
-        ASTNode::FunctionCall(FunctionCallNode { name, arguments })
+    pub fn create_interpolation_node(parts: Vec<ASTNode>, span: Span) -> ASTNode {
+        ASTNode::Interpolation(InterpolationNode { parts, span })
     }
 
-    pub fn create_return_node(value: ASTNode) -> ASTNode {
+    pub fn create_number_literal_node(value: Numeric, span: Span) -> ASTNode {
+        ASTNode::NumberLiteral(NumberLiteralNode { value, span })
+    }
+
+    pub fn create_bool_literal_node(value: i8, span: Span) -> ASTNode {
+        ASTNode::BoolLiteral(BoolLiteralNode { value, span })
+    }
+
+    pub fn create_nil_literal_node(span: Span) -> ASTNode {
+        ASTNode::NilLiteral(NilLiteralNode { span })
+    }
+
+    pub fn create_function_call_node(callee: ASTNode, arguments: Vec<ASTNode>, span: Span) -> ASTNode {
+        ASTNode::FunctionCall(FunctionCallNode {
+            callee: Box::new(callee),
+            arguments,
+            span,
+        })
+    }
+
+    pub fn create_return_node(value: Option<ASTNode>, span: Span) -> ASTNode {
         ASTNode::Return(ReturnNode {
-            value: Box::new(value),
+            value: value.map(Box::new),
+            span,
         })
     }
 
-    pub fn create_kind_of_node(expression: ASTNode) -> ASTNode {
+    pub fn create_kind_of_node(expression: ASTNode, span: Span) -> ASTNode {
         ASTNode::KindOf(KindOfNode {
             expression: Box::new(expression),
+            span,
+        })
+    }
+
+    pub fn create_if_node(
+        condition: ASTNode,
+        body: Vec<ASTNode>,
+        else_body: Option<Vec<ASTNode>>,
+        span: Span,
+    ) -> ASTNode {
+        ASTNode::If(IfNode {
+            condition: Box::new(condition),
+            body,
+            else_body,
+            span,
+        })
+    }
+
+    pub fn create_while_node(condition: ASTNode, body: Vec<ASTNode>, span: Span) -> ASTNode {
+        ASTNode::While(WhileNode {
+            condition: Box::new(condition),
+            body,
+            span,
+        })
+    }
+
+    pub fn create_for_node(
+        variable: String,
+        iterable: ASTNode,
+        body: Vec<ASTNode>,
+        span: Span,
+    ) -> ASTNode {
+        ASTNode::For(ForNode {
+            variable,
+            iterable: Box::new(iterable),
+            body,
+            span,
+        })
+    }
+
+    pub fn create_range_node(
+        start: ASTNode,
+        end: ASTNode,
+        step: Option<ASTNode>,
+        span: Span,
+    ) -> ASTNode {
+        ASTNode::Range(RangeNode {
+            start: Box::new(start),
+            end: Box::new(end),
+            step: step.map(Box::new),
+            span,
         })
     }
+
+    pub fn create_list_node(elements: Vec<ASTNode>, span: Span) -> ASTNode {
+        ASTNode::List(ListNode { elements, span })
+    }
+
+    pub fn create_map_node(entries: Vec<(ASTNode, ASTNode)>, span: Span) -> ASTNode {
+        ASTNode::Map(MapNode { entries, span })
+    }
+
+    pub fn create_index_node(target: ASTNode, index: ASTNode, span: Span) -> ASTNode {
+        ASTNode::Index(IndexNode {
+            target: Box::new(target),
+            index: Box::new(index),
+            span,
+        })
+    }
+
+    pub fn create_member_access_node(target: ASTNode, member: String, span: Span) -> ASTNode {
+        ASTNode::MemberAccess(MemberAccessNode {
+            target: Box::new(target),
+            member,
+            span,
+        })
+    }
+
+    pub fn create_super_node(method: String, span: Span) -> ASTNode {
+        ASTNode::Super(SuperNode { method, span })
+    }
+
+    pub fn create_slice_node(
+        target: ASTNode,
+        start: Option<ASTNode>,
+        end: Option<ASTNode>,
+        span: Span,
+    ) -> ASTNode {
+        ASTNode::Slice(SliceNode {
+            target: Box::new(target),
+            start: start.map(Box::new),
+            end: end.map(Box::new),
+            span,
+        })
+    }
+
+    pub fn create_break_node(span: Span) -> ASTNode {
+        ASTNode::Break(BreakNode { span })
+    }
+
+    pub fn create_continue_node(span: Span) -> ASTNode {
+        ASTNode::Continue(ContinueNode { span })
+    }
+
+    pub fn create_error_node(span: Span) -> ASTNode {
+        ASTNode::Error(ErrorNode { span })
+    }
 }
@@ -0,0 +1,132 @@
+use std::fmt;
+
+use crate::lexer::tokens::TokenType;
+use crate::parser::ast::ASTNode;
+use crate::utils::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub enum ParseError {
+    UnexpectedToken { found: TokenType, context: &'static str, span: Span },
+    UnexpectedEndOfFile { span: Span },
+    Expected { expected: String, found: TokenType, span: Span },
+    MissingName { after: &'static str, span: Span },
+    KeywordAsParameter { span: Span },
+    InvalidNumber { literal: String, span: Span },
+    ReturnOutsideFunction { span: Span },
+    JumpOutsideLoop { keyword: String, span: Span },
+    InvalidAssignmentTarget { span: Span },
+    ConstantAssignment { name: String, span: Span },
+    ConstantRedeclaration { name: String, span: Span },
+    AlreadyDeclared { name: String, span: Span },
+    DuplicateField { name: String, span: Span },
+    DuplicateMethod { name: String, span: Span },
+    InvalidClassMember { span: Span },
+    OutsideClass { keyword: &'static str, span: Span },
+    NoParentClass { span: Span },
+    DuplicateVariant { name: String, span: Span },
+    UnknownVariant { enum_name: String, variant: String, span: Span },
+    PatternArity { variant: String, expected: usize, found: usize, span: Span },
+    NonExhaustiveMatch { enum_name: String, missing: Vec<String>, span: Span },
+}
+
+/// Every syntax error found in a file, along with the partial tree.
+/// Statements that failed to parse are replaced by `ASTNode::Error`.
+#[derive(Debug, Clone)]
+pub struct ParseFailure {
+    #[allow(dead_code)] // Not used by the interpreter, kept for tooling inspecting broken files
+    pub ast: Vec<ASTNode>,
+    pub errors: Vec<ParseError>,
+}
+
+impl ParseError {
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::UnexpectedToken { span, .. }
+            | ParseError::UnexpectedEndOfFile { span }
+            | ParseError::Expected { span, .. }
+            | ParseError::MissingName { span, .. }
+            | ParseError::KeywordAsParameter { span }
+            | ParseError::InvalidNumber { span, .. }
+            | ParseError::ReturnOutsideFunction { span }
+            | ParseError::JumpOutsideLoop { span, .. }
+            | ParseError::InvalidAssignmentTarget { span }
+            | ParseError::ConstantAssignment { span, .. }
+            | ParseError::ConstantRedeclaration { span, .. }
+            | ParseError::AlreadyDeclared { span, .. }
+            | ParseError::DuplicateField { span, .. }
+            | ParseError::DuplicateMethod { span, .. }
+            | ParseError::InvalidClassMember { span }
+            | ParseError::OutsideClass { span, .. }
+            | ParseError::NoParentClass { span }
+            | ParseError::DuplicateVariant { span, .. }
+            | ParseError::UnknownVariant { span, .. }
+            | ParseError::PatternArity { span, .. }
+            | ParseError::NonExhaustiveMatch { span, .. } => *span,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedToken { found, context, .. } => {
+                write!(f, "Unexpected token {}: {:?}", context, found)
+            }
+            ParseError::UnexpectedEndOfFile { .. } => write!(f, "Unexpected end of file"),
+            ParseError::Expected { expected, found, .. } => {
+                write!(f, "Expected {}, but got {:?}", expected, found)
+            }
+            ParseError::MissingName { after, .. } => write!(f, "Expected name after {}", after),
+            ParseError::KeywordAsParameter { .. } => {
+                write!(f, "You can't use a keyword as a function parameter.")
+            }
+            ParseError::InvalidNumber { literal, .. } => {
+                write!(f, "Invalid number literal: {}", literal)
+            }
+            ParseError::ReturnOutsideFunction { .. } => {
+                write!(f, "bringback can only be used inside a sparkle")
+            }
+            ParseError::JumpOutsideLoop { keyword, .. } => {
+                write!(f, "{} can only be used inside a loop", keyword)
+            }
+            ParseError::InvalidAssignmentTarget { .. } => write!(f, "Only variables, fields, list items and map entries can be assigned to"),
+            ParseError::ConstantAssignment { name, .. } => {
+                write!(f, "Can't assign to \"{}\", it is a fowever constant", name)
+            }
+            ParseError::ConstantRedeclaration { name, .. } => {
+                write!(f, "\"{}\" is a fowever constant, it can't be declared again", name)
+            }
+            ParseError::AlreadyDeclared { name, .. } => write!(
+                f,
+                "\"{}\" is already declared in this scope, a fowever needs a name of its own",
+                name
+            ),
+            ParseError::DuplicateField { name, .. } => write!(f, "Field \"{}\" is listed twice", name),
+            ParseError::DuplicateMethod { name, .. } => {
+                write!(f, "Method \"{}\" is declared twice in this kwass", name)
+            }
+            ParseError::InvalidClassMember { .. } => {
+                write!(f, "A kwass body can only hold sparkle methods")
+            }
+            ParseError::OutsideClass { keyword, .. } => {
+                write!(f, "{} can only be used inside the methods of a kwass", keyword)
+            }
+            ParseError::NoParentClass { .. } => {
+                write!(f, "supew needs a parent kwass, like in kwass Dog < Animal")
+            }
+            ParseError::DuplicateVariant { name, .. } => write!(f, "Variant \"{}\" is listed twice", name),
+            ParseError::UnknownVariant { enum_name, variant, .. } => {
+                write!(f, "{} has no variant \"{}\"", enum_name, variant)
+            }
+            ParseError::PatternArity { variant, expected, found, .. } => write!(
+                f,
+                "{} carries {} values, but the pattern has {}",
+                variant, expected, found
+            ),
+            ParseError::NonExhaustiveMatch { enum_name, missing, .. } => {
+                let missing: Vec<String> = missing.iter().map(|variant| format!("{}.{}", enum_name, variant)).collect();
+                write!(f, "This meowtch misses {}, add arms for them or a _ arm", missing.join(", "))
+            }
+        }
+    }
+}
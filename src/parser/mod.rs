@@ -1,3 +1,6 @@
 pub mod ast;
 pub mod ast_builder;
-pub mod parser;
\ No newline at end of file
+pub mod errors;
+#[allow(clippy::module_inception)]
+pub mod parser;
+pub mod resolver;
@@ -1,269 +1,1411 @@
+use num_bigint::BigInt;
+
 use crate::lexer::tokens::{Token, TokenType};
 use crate::parser::ast::*;
 use crate::parser::ast_builder::ASTBuilder;
+use crate::parser::errors::{ParseError, ParseFailure};
+use crate::utils::decimal::Decimal;
+use crate::utils::diagnostics::Span;
 
-pub fn parse(tokens: &[Token]) -> Vec<ASTNode> {
+pub fn parse(tokens: &[Token]) -> Result<Vec<ASTNode>, ParseFailure> {
+    let tokens = &without_dangling_doc_comments(tokens);
+    let mut parser = Parser {
+        tokens,
+        current: 0,
+        function_depth: 0,
+        loop_depth: 0,
+        class_context: None,
+        errors: Vec::new(),
+    };
     let mut ast = Vec::new();
-    let mut current = 0;
 
-    while current < tokens.len() {
-        ast.push(walk(tokens, &mut current));
+    while parser.current < tokens.len() {
+        let start = parser.current;
+        match parser.walk() {
+            Ok(node) => ast.push(node),
+            Err(error) => {
+                ast.push(parser.recover(error, start));
+
+                // A '}' left over from a broken block would only report the same problem again
+                if parser.check(TokenType::Brace, "}") {
+                    parser.current += 1;
+                }
+            }
+        }
     }
 
     // println!("{:?}", ast);
-    ast
+    if parser.errors.is_empty() {
+        Ok(ast)
+    } else {
+        Err(ParseFailure {
+            ast,
+            errors: parser.errors,
+        })
+    }
 }
 
-// === Expression Parsing ===
-
-fn parse_expression(tokens: &[Token], current: &mut usize) -> ASTNode {
-    parse_additive_expression(tokens, current)
+struct Parser<'a> {
+    tokens: &'a [Token],
+    current: usize,
+    /// Number of `sparkle` bodies we are currently in, `bringback` is only valid above 0.
+    function_depth: usize,
+    /// Number of loops around the current statement in this function, for `bweak` and `continyu`.
+    loop_depth: usize,
+    /// Set inside a `kwass` body, to whether it has a parent. Tells where `sewf` and `supew` are allowed.
+    class_context: Option<bool>,
+    errors: Vec<ParseError>,
 }
 
-fn parse_additive_expression(tokens: &[Token], current: &mut usize) -> ASTNode {
-    let mut node = parse_multiplicative_expression(tokens, current);
+impl<'a> Parser<'a> {
+    // === Expression Parsing ===
+
+    fn parse_expression(&mut self) -> Result<ASTNode, ParseError> {
+        self.parse_or_expression()
+    }
 
-    while let Some(token) = tokens.get(*current) {
-        if token.token_type == TokenType::Operator && (token.value == "+" || token.value == "-") {
-            let operator = token.value.clone();
-            *current += 1;
-            let right = parse_multiplicative_expression(tokens, current);
+    fn parse_or_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_and_expression()?;
+
+        while self.match_operator(&["||", "or"]).is_some() {
+            let right = self.parse_and_expression()?;
+            node = ASTBuilder::create_logical_expression_node("||".to_string(), node, right);
+        }
+
+        Ok(node)
+    }
+
+    fn parse_and_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_equality_expression()?;
+
+        while self.match_operator(&["&&", "and"]).is_some() {
+            let right = self.parse_equality_expression()?;
+            node = ASTBuilder::create_logical_expression_node("&&".to_string(), node, right);
+        }
+
+        Ok(node)
+    }
+
+    fn parse_equality_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_comparison_expression()?;
+
+        while let Some(operator) = self.match_operator(&["==", "!="]) {
+            let right = self.parse_comparison_expression()?;
             node = ASTBuilder::create_binary_expression_node(operator, node, right);
-        } else {
-            break;
         }
+
+        Ok(node)
     }
 
-    node
-}
+    fn parse_comparison_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_bitwise_or_expression()?;
+
+        while let Some(operator) = self.match_operator(&["<", "<=", ">", ">="]) {
+            let right = self.parse_bitwise_or_expression()?;
+            node = ASTBuilder::create_binary_expression_node(operator, node, right);
+        }
 
-fn parse_multiplicative_expression(tokens: &[Token], current: &mut usize) -> ASTNode {
-    let mut node = parse_primary_expression(tokens, current);
+        Ok(node)
+    }
+
+    /// Bitwise operators bind tighter than comparisons, so `n & 1 == 0` checks `n & 1`.
+    fn parse_bitwise_or_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_bitwise_xor_expression()?;
 
-    while let Some(token) = tokens.get(*current) {
-        if token.token_type == TokenType::Operator && (token.value == "*" || token.value == "/") {
-            let operator = token.value.clone();
-            *current += 1;
-            let right = parse_primary_expression(tokens, current);
+        while let Some(operator) = self.match_operator(&["|"]) {
+            let right = self.parse_bitwise_xor_expression()?;
             node = ASTBuilder::create_binary_expression_node(operator, node, right);
-        } else {
-            break;
         }
+
+        Ok(node)
     }
 
-    node
-}
+    fn parse_bitwise_xor_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_bitwise_and_expression()?;
+
+        while let Some(operator) = self.match_operator(&["^"]) {
+            let right = self.parse_bitwise_and_expression()?;
+            node = ASTBuilder::create_binary_expression_node(operator, node, right);
+        }
+
+        Ok(node)
+    }
+
+    fn parse_bitwise_and_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_shift_expression()?;
+
+        while let Some(operator) = self.match_operator(&["&"]) {
+            let right = self.parse_shift_expression()?;
+            node = ASTBuilder::create_binary_expression_node(operator, node, right);
+        }
+
+        Ok(node)
+    }
 
-fn parse_primary_expression(tokens: &[Token], current: &mut usize) -> ASTNode {
-    let token = &tokens[*current];
+    fn parse_shift_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_additive_expression()?;
 
-    match token.token_type {
-        TokenType::Number => {
-            *current += 1;
-            let value = token.value.parse::<f64>().unwrap();
-            ASTBuilder::create_number_literal_node(value)
+        while let Some(operator) = self.match_operator(&["<<", ">>"]) {
+            let right = self.parse_additive_expression()?;
+            node = ASTBuilder::create_binary_expression_node(operator, node, right);
         }
-        TokenType::String => {
-            *current += 1;
-            ASTBuilder::create_string_literal_node(token.value.clone())
+
+        Ok(node)
+    }
+
+    fn parse_additive_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_multiplicative_expression()?;
+
+        while let Some(operator) = self.match_operator(&["+", "-"]) {
+            let right = self.parse_multiplicative_expression()?;
+            node = ASTBuilder::create_binary_expression_node(operator, node, right);
         }
-        TokenType::Bool => {
-            *current += 1;
-            if token.value == "twue" {
-                ASTBuilder::create_bool_literal_node(1)
+
+        Ok(node)
+    }
+
+    fn parse_multiplicative_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let mut node = self.parse_unary_expression()?;
+
+        while let Some(operator) = self.match_operator(&["*", "/", "//", "%"]) {
+            let right = self.parse_unary_expression()?;
+            node = ASTBuilder::create_binary_expression_node(operator, node, right);
+        }
+
+        Ok(node)
+    }
+
+    fn parse_unary_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+
+        if self.match_operator(&["!", "not"]).is_some() {
+            let operand = self.parse_unary_expression()?;
+            let span = self.tokens[start].span.to(operand.span());
+            return Ok(ASTBuilder::create_unary_expression_node("!".to_string(), operand, span));
+        }
+        if let Some(operator) = self.match_operator(&["-", "+", "~"]) {
+            let operand = self.parse_unary_expression()?;
+            let span = self.tokens[start].span.to(operand.span());
+            return Ok(ASTBuilder::create_unary_expression_node(operator, operand, span));
+        }
+
+        self.parse_power_expression()
+    }
+
+    /// `**` binds tighter than the unary operators on its left and groups to the right,
+    /// so `2 ** 3 ** 2` is `2 ** 9`. Its right side may start with one, as in `2 ** ~1`.
+    fn parse_power_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let node = self.parse_postfix_expression()?;
+
+        if let Some(operator) = self.match_operator(&["**"]) {
+            let right = self.parse_unary_expression()?;
+            return Ok(ASTBuilder::create_binary_expression_node(operator, node, right));
+        }
+
+        Ok(node)
+    }
+
+    fn parse_postfix_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        let mut node = self.parse_primary_expression()?;
+
+        // A '(' or '[' on the next line starts a new statement instead of applying to this expression,
+        // a '.' can't start a statement so it always continues it
+        loop {
+            if self.check_type(TokenType::Dot) {
+                self.current += 1;
+                let member = self.peek()?;
+                if member.token_type != TokenType::Identifier {
+                    return Err(ParseError::Expected {
+                        expected: "field name".to_string(),
+                        found: member.token_type.clone(),
+                        span: member.span,
+                    });
+                }
+                self.current += 1;
+                node = ASTBuilder::create_member_access_node(node, member.value.clone(), self.span_from(start));
+            } else if !self.on_previous_line() {
+                break;
+            } else if self.check(TokenType::Parenthesis, "(") {
+                self.current += 1;
+                let arguments = self.parse_arguments()?;
+                node = ASTBuilder::create_function_call_node(node, arguments, self.span_from(start));
+            } else if self.check(TokenType::Bracket, "[") {
+                self.current += 1;
+                node = self.parse_index(node, start)?;
             } else {
-                ASTBuilder::create_bool_literal_node(0)
+                break;
+            }
+        }
+
+        Ok(node)
+    }
+
+    /// Parses `[index]` or a `[start..end]` slice after `target`, the opening '[' being already consumed.
+    fn parse_index(&mut self, target: ASTNode, start: usize) -> Result<ASTNode, ParseError> {
+        let lower = if self.check_type(TokenType::Range) {
+            None
+        } else {
+            let index = self.parse_expression()?;
+            if !self.check_type(TokenType::Range) {
+                self.expect_bracket("]")?;
+                return Ok(ASTBuilder::create_index_node(target, index, self.span_from(start)));
+            }
+            Some(index)
+        };
+
+        self.current += 1; // Skip '..'
+        let upper = if self.check(TokenType::Bracket, "]") {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.expect_bracket("]")?;
+
+        Ok(ASTBuilder::create_slice_node(target, lower, upper, self.span_from(start)))
+    }
+
+    /// Parses a `[a, b, c]` list literal, a trailing comma is allowed.
+    fn parse_list(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip '['
+
+        let mut elements = Vec::new();
+        while !self.check(TokenType::Bracket, "]") {
+            elements.push(self.parse_expression()?);
+            if !self.check_type(TokenType::Comma) {
+                break;
             }
+            self.current += 1;
         }
-        TokenType::Identifier => parse_identifier(tokens, current),
-        TokenType::Parenthesis if token.value == "(" => {
-            *current += 1;
-            let expr = parse_expression(tokens, current);
-            expect_parenthesis(tokens, current, ")");
-            expr
+
+        self.expect_bracket("]")?;
+        Ok(ASTBuilder::create_list_node(elements, self.span_from(start)))
+    }
+
+    /// Parses a string holding `{expression}`s. The lexer gives the text before each expression
+    /// as a `StringPart`, then the expression's tokens and an `InterpolationEnd`; the text after
+    /// the last expression is a plain `String`.
+    fn parse_interpolation(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        let mut parts = Vec::new();
+
+        loop {
+            let text = self.peek()?;
+            self.current += 1;
+            if !text.value.is_empty() {
+                parts.push(ASTBuilder::create_string_literal_node(text.value.clone(), text.span));
+            }
+            if text.token_type == TokenType::String {
+                break;
+            }
+
+            parts.push(self.parse_expression()?);
+            let end = self.peek()?;
+            if end.token_type != TokenType::InterpolationEnd {
+                return Err(ParseError::Expected {
+                    expected: "'}' to end the expression in the string".to_string(),
+                    found: end.token_type.clone(),
+                    span: end.span,
+                });
+            }
+            self.current += 1;
         }
-        TokenType::KindOf => parse_kind_of(tokens, current),
-        _ => panic!("Unexpected token in expression: {:?}", token.token_type),
+
+        Ok(ASTBuilder::create_interpolation_node(parts, self.span_from(start)))
     }
-}
 
-// === Statement Parsing ===
-
-fn walk(tokens: &[Token], current: &mut usize) -> ASTNode {
-    match tokens[*current].token_type {
-        TokenType::Print => parse_print(tokens, current),
-        TokenType::OwO => parse_owo(tokens, current),
-        TokenType::KindOf => parse_kind_of(tokens, current),
-        TokenType::FunctionDef => parse_function_declaration(tokens, current),
-        TokenType::VarDecl => parse_variable_declaration(tokens, current),
-        TokenType::Identifier => parse_identifier(tokens, current),
-        _ => panic!(
-            "Unexpected token at top level: {:?}",
-            tokens[*current].token_type
-        ),
+    fn parse_primary_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let token = self.peek()?;
+
+        match token.token_type {
+            TokenType::Number => {
+                self.current += 1;
+                let value = number_value(&token.value).ok_or_else(|| ParseError::InvalidNumber {
+                    literal: token.value.clone(),
+                    span: token.span,
+                })?;
+                Ok(ASTBuilder::create_number_literal_node(value, token.span))
+            }
+            TokenType::String => {
+                self.current += 1;
+                Ok(ASTBuilder::create_string_literal_node(token.value.clone(), token.span))
+            }
+            TokenType::StringPart => self.parse_interpolation(),
+            TokenType::Bool => {
+                self.current += 1;
+                if token.value == "twue" {
+                    Ok(ASTBuilder::create_bool_literal_node(1, token.span))
+                } else {
+                    Ok(ASTBuilder::create_bool_literal_node(0, token.span))
+                }
+            }
+            TokenType::Nil => {
+                self.current += 1;
+                Ok(ASTBuilder::create_nil_literal_node(token.span))
+            }
+            TokenType::Identifier => self.parse_identifier(),
+            TokenType::Parenthesis if token.value == "(" => {
+                self.current += 1;
+                let expr = self.parse_expression()?;
+                self.expect_parenthesis(")")?;
+                Ok(expr)
+            }
+            TokenType::Bracket if token.value == "[" => self.parse_list(),
+            TokenType::Brace if token.value == "{" => self.parse_map(),
+            TokenType::KindOf => self.parse_kind_of(),
+            TokenType::FunctionDef => self.parse_lambda(),
+            TokenType::SelfRef => {
+                if self.class_context.is_none() {
+                    return Err(ParseError::OutsideClass {
+                        keyword: "sewf",
+                        span: token.span,
+                    });
+                }
+                self.current += 1;
+                Ok(ASTBuilder::create_variable_reference_node(token.value.clone(), token.span))
+            }
+            TokenType::Super => self.parse_super(),
+            TokenType::Match => self.parse_match(false),
+            _ => Err(ParseError::UnexpectedToken {
+                found: token.token_type.clone(),
+                context: "in expression",
+                span: token.span,
+            }),
+        }
     }
-}
 
-fn parse_print(tokens: &[Token], current: &mut usize) -> ASTNode {
-    *current += 1;
-    expect_parenthesis(tokens, current, "(");
-    let expr = parse_expression(tokens, current);
-    expect_parenthesis(tokens, current, ")");
-    ASTBuilder::create_print_node(expr)
-}
+    // === Statement Parsing ===
 
-fn parse_owo(tokens: &[Token], current: &mut usize) -> ASTNode {
-    *current += 1;
-    expect_parenthesis(tokens, current, "(");
-    let expr = parse_expression(tokens, current);
-    expect_parenthesis(tokens, current, ")");
-    ASTBuilder::create_owo_node(expr)
-}
+    fn walk(&mut self) -> Result<ASTNode, ParseError> {
+        let token = self.peek()?;
 
-fn parse_kind_of(tokens: &[Token], current: &mut usize) -> ASTNode {
-    *current += 1;
-    expect_parenthesis(tokens, current, "(");
-    let expr = parse_expression(tokens, current);
-    expect_parenthesis(tokens, current, ")");
-    ASTBuilder::create_kind_of_node(expr)
-}
+        match token.token_type {
+            TokenType::Print => self.parse_print(),
+            TokenType::OwO => self.parse_owo(),
+            TokenType::KindOf => self.parse_kind_of(),
+            TokenType::FunctionDef => self.parse_function_declaration(),
+            TokenType::Record => self.parse_record_declaration(),
+            TokenType::Class => self.parse_class_declaration(),
+            TokenType::Enum => self.parse_enum_declaration(),
+            TokenType::VarDecl | TokenType::ConstDecl => self.parse_variable_declaration(),
+            TokenType::Match => self.parse_match(true),
+            TokenType::Identifier | TokenType::SelfRef | TokenType::Super => self.parse_expression_statement(),
+            TokenType::Return => self.parse_return(),
+            TokenType::If => self.parse_if(),
+            TokenType::While => self.parse_while(),
+            TokenType::For => self.parse_for(),
+            TokenType::Break | TokenType::Continue => self.parse_loop_jump(),
+            TokenType::DocComment => self.parse_documented_declaration(),
+            _ => Err(ParseError::UnexpectedToken {
+                found: token.token_type.clone(),
+                context: "at top level",
+                span: token.span,
+            }),
+        }
+    }
+
+    /// Collects consecutive `##` lines and attaches them to the `sparkle`, `bento`, `flavor`, `kwass`, `nyan` or `fowever` that follows.
+    fn parse_documented_declaration(&mut self) -> Result<ASTNode, ParseError> {
+        let mut lines = Vec::new();
+
+        while self.check_type(TokenType::DocComment) {
+            lines.push(self.tokens[self.current].value.clone());
+            self.current += 1;
+        }
+        let doc = Some(lines.join("\n"));
+
+        let mut node = match self.tokens.get(self.current).map(|t| &t.token_type) {
+            Some(TokenType::FunctionDef) => self.parse_function_declaration()?,
+            Some(TokenType::Record) => self.parse_record_declaration()?,
+            Some(TokenType::Class) => self.parse_class_declaration()?,
+            Some(TokenType::Enum) => self.parse_enum_declaration()?,
+            Some(TokenType::VarDecl | TokenType::ConstDecl) => self.parse_variable_declaration()?,
+            _ => unreachable!("doc comments above anything else are dropped before parsing"),
+        };
+
+        match &mut node {
+            ASTNode::FunctionDeclaration(func) => func.doc = doc,
+            ASTNode::RecordDeclaration(record) => record.doc = doc,
+            ASTNode::ClassDeclaration(class) => class.doc = doc,
+            ASTNode::EnumDeclaration(enum_node) => enum_node.doc = doc,
+            ASTNode::VariableDeclaration(var) => var.doc = doc,
+            _ => {}
+        }
+        Ok(node)
+    }
+
+    fn parse_print(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1;
+        self.expect_parenthesis("(")?;
+        let expr = self.parse_expression()?;
+        self.expect_parenthesis(")")?;
+        Ok(ASTBuilder::create_print_node(expr, self.span_from(start)))
+    }
+
+    fn parse_owo(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1;
+        self.expect_parenthesis("(")?;
+        let expr = self.parse_expression()?;
+        self.expect_parenthesis(")")?;
+        Ok(ASTBuilder::create_owo_node(expr, self.span_from(start)))
+    }
+
+    fn parse_kind_of(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1;
+        self.expect_parenthesis("(")?;
+        let expr = self.parse_expression()?;
+        self.expect_parenthesis(")")?;
+        Ok(ASTBuilder::create_kind_of_node(expr, self.span_from(start)))
+    }
 
-fn parse_function_declaration(tokens: &[Token], current: &mut usize) -> ASTNode {
-    *current += 1;
+    fn parse_function_declaration(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1;
 
-    let name_token = &tokens[*current];
-    if name_token.token_type != TokenType::Identifier {
-        panic!("Expected function name after sparkle");
+        let name_token = self.peek()?;
+        if name_token.token_type != TokenType::Identifier {
+            return Err(ParseError::MissingName {
+                after: "sparkle",
+                span: name_token.span,
+            });
+        }
+
+        let name = name_token.value.clone();
+        self.current += 1;
+
+        let params = self.parse_params()?;
+        let header_span = self.span_from(start);
+        let body = self.parse_function_body()?;
+
+        Ok(ASTBuilder::create_function_declaration_node(name, params, body, header_span))
     }
 
-    let name = name_token.value.clone();
-    *current += 1;
-    expect_parenthesis(tokens, current, "(");
+    /// Parses `bento Name { field, ... }`, fields are separated by commas.
+    fn parse_record_declaration(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip 'bento'
 
-    let mut params = Vec::new();
-    while let Some(token) = tokens.get(*current) {
-        if token.token_type == TokenType::Parenthesis && token.value == ")" {
-            break;
+        let name_token = self.peek()?;
+        if name_token.token_type != TokenType::Identifier {
+            return Err(ParseError::MissingName {
+                after: "bento",
+                span: name_token.span,
+            });
         }
+        self.current += 1;
+        let header_span = self.span_from(start);
+
+        self.expect_brace("{")?;
+        let mut fields = Vec::new();
+        while !self.check(TokenType::Brace, "}") {
+            let field = self.parse_field_name(&fields)?;
+            fields.push(field);
 
-        if token.token_type == TokenType::OwO || token.token_type == TokenType::Print || token.token_type == TokenType::FunctionDef || token.token_type == TokenType::VarDecl || token.token_type == TokenType::Return || token.token_type == TokenType::KindOf {
-            panic!("You can't use a keyword as a function parameter.")
-        } 
-        
-        if token.token_type == TokenType::Identifier {
-            let param_name = token.value.clone();
-            params.push(ASTNode::VariableDeclaration(VariableDeclarationNode {
-                name: param_name,
-                value: Box::new(ASTNode::None), // Placeholder value
-            }));
-            *current += 1;
+            if !self.check_type(TokenType::Comma) {
+                break;
+            }
+            self.current += 1;
         }
-        
-        if token.token_type == TokenType::Comma {
-            *current += 1; // Skip comma and continue to next parameter
+        self.expect_brace("}")?;
+
+        Ok(ASTBuilder::create_record_declaration_node(name_token.value.clone(), fields, header_span))
+    }
+
+    /// Parses `flavor Name { Variant, Variant(field, ...), ... }`, variants are separated by commas.
+    fn parse_enum_declaration(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip 'flavor'
+
+        let name_token = self.peek()?;
+        if name_token.token_type != TokenType::Identifier {
+            return Err(ParseError::MissingName {
+                after: "flavor",
+                span: name_token.span,
+            });
+        }
+        self.current += 1;
+        let header_span = self.span_from(start);
+
+        self.expect_brace("{")?;
+        let mut variants: Vec<EnumVariant> = Vec::new();
+        while !self.check(TokenType::Brace, "}") {
+            let variant = self.peek()?;
+            if variant.token_type != TokenType::Identifier {
+                return Err(ParseError::Expected {
+                    expected: "variant name".to_string(),
+                    found: variant.token_type.clone(),
+                    span: variant.span,
+                });
+            }
+            if variants.iter().any(|v| v.name == variant.value) {
+                return Err(ParseError::DuplicateVariant {
+                    name: variant.value.clone(),
+                    span: variant.span,
+                });
+            }
+            self.current += 1;
+
+            let mut fields = Vec::new();
+            if self.check(TokenType::Parenthesis, "(") {
+                self.current += 1;
+                while !self.check(TokenType::Parenthesis, ")") {
+                    let field = self.parse_field_name(&fields)?;
+                    fields.push(field);
+
+                    if !self.check_type(TokenType::Comma) {
+                        break;
+                    }
+                    self.current += 1;
+                }
+                self.expect_parenthesis(")")?;
+            }
+            variants.push(EnumVariant {
+                name: variant.value.clone(),
+                fields,
+            });
+
+            if !self.check_type(TokenType::Comma) {
+                break;
+            }
+            self.current += 1;
         }
+        self.expect_brace("}")?;
+
+        Ok(ASTBuilder::create_enum_declaration_node(name_token.value.clone(), variants, header_span))
+    }
+
+    /// Reads the name of a `bento` field or of a value carried by a `flavor` variant,
+    /// `fields` being the names listed before it.
+    fn parse_field_name(&mut self, fields: &[String]) -> Result<String, ParseError> {
+        let field = self.peek()?;
+        if field.token_type != TokenType::Identifier {
+            return Err(ParseError::Expected {
+                expected: "field name".to_string(),
+                found: field.token_type.clone(),
+                span: field.span,
+            });
+        }
+        if fields.contains(&field.value) {
+            return Err(ParseError::DuplicateField {
+                name: field.value.clone(),
+                span: field.span,
+            });
+        }
+        self.current += 1;
+        Ok(field.value.clone())
     }
 
-    expect_parenthesis(tokens, current, ")");
-    expect_brace(tokens, current, "{");
+    /// Parses `kwass Name { ... }`, or `kwass Name < Parent { ... }` to inherit the methods of `Parent`.
+    fn parse_class_declaration(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip 'kwass'
 
-    let mut body = Vec::new();
-    while tokens.get(*current).map_or(false, |t| {
-        t.token_type != TokenType::Brace || t.value != "}"
-    }) {
-        if tokens[*current].token_type == TokenType::Return {
-            body.push(parse_return(tokens, current));
+        let name_token = self.peek()?;
+        if name_token.token_type != TokenType::Identifier {
+            return Err(ParseError::MissingName {
+                after: "kwass",
+                span: name_token.span,
+            });
+        }
+        self.current += 1;
+
+        let superclass = if self.match_operator(&["<"]).is_some() {
+            let parent = self.peek()?;
+            if parent.token_type != TokenType::Identifier {
+                return Err(ParseError::Expected {
+                    expected: "parent kwass name".to_string(),
+                    found: parent.token_type.clone(),
+                    span: parent.span,
+                });
+            }
+            self.current += 1;
+            Some(ASTBuilder::create_variable_reference_node(parent.value.clone(), parent.span))
         } else {
-            body.push(walk(tokens, current));
+            None
+        };
+        let header_span = self.span_from(start);
+
+        let outer_class = self.class_context.replace(superclass.is_some());
+        let methods = self.parse_class_body();
+        self.class_context = outer_class;
+
+        Ok(ASTBuilder::create_class_declaration_node(
+            name_token.value.clone(),
+            superclass,
+            methods?,
+            header_span,
+        ))
+    }
+
+    /// Parses the methods between the braces of a `kwass`, recovering from broken ones like `parse_block`.
+    fn parse_class_body(&mut self) -> Result<Vec<FunctionDeclarationNode>, ParseError> {
+        self.expect_brace("{")?;
+
+        let mut methods: Vec<FunctionDeclarationNode> = Vec::new();
+        while self.tokens.get(self.current).is_some_and(|t| {
+            t.token_type != TokenType::Brace || t.value != "}"
+        }) {
+            let member_start = self.current;
+            let member = match self.tokens[member_start].token_type {
+                TokenType::FunctionDef | TokenType::DocComment => self.walk(),
+                _ => Err(ParseError::InvalidClassMember {
+                    span: self.tokens[member_start].span,
+                }),
+            };
+
+            match member {
+                Ok(ASTNode::FunctionDeclaration(method)) => {
+                    if methods.iter().any(|m| m.name == method.name) {
+                        let error = ParseError::DuplicateMethod {
+                            name: method.name.clone(),
+                            span: method.span,
+                        };
+                        self.errors.push(error);
+                    }
+                    methods.push(method);
+                }
+                Ok(other) => self.errors.push(ParseError::InvalidClassMember { span: other.span() }),
+                Err(error) => {
+                    self.recover(error, member_start);
+                }
+            }
         }
+
+        self.expect_brace("}")?;
+        Ok(methods)
     }
 
-    *current += 1; // Skip '}'
-    ASTBuilder::create_function_declaration_node(name, params, body)
-}
+    /// Parses `supew.method`, only valid in a `kwass` that has a parent.
+    fn parse_super(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        let keyword = &self.tokens[start];
+        match self.class_context {
+            None => {
+                return Err(ParseError::OutsideClass {
+                    keyword: "supew",
+                    span: keyword.span,
+                });
+            }
+            Some(false) => return Err(ParseError::NoParentClass { span: keyword.span }),
+            Some(true) => {}
+        }
+        self.current += 1;
 
-fn parse_return(tokens: &[Token], current: &mut usize) -> ASTNode {
-    *current += 1; // Skip 'bringback'
-    let value = parse_expression(tokens, current);
-    ASTBuilder::create_return_node(value)
-}
+        self.expect_token_type(TokenType::Dot)?;
+        let method = self.peek()?;
+        if method.token_type != TokenType::Identifier {
+            return Err(ParseError::Expected {
+                expected: "method name".to_string(),
+                found: method.token_type.clone(),
+                span: method.span,
+            });
+        }
+        self.current += 1;
+
+        Ok(ASTBuilder::create_super_node(method.value.clone(), self.span_from(start)))
+    }
+
+    /// Parses `meowtch subject { pattern => body, ... }`, each pattern can be followed by a
+    /// `nani condition` guard. Used as a statement, the arm bodies are blocks or single statements;
+    /// used as a value, they are expressions (so a '{' there starts a map, not a block).
+    fn parse_match(&mut self, statement: bool) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip 'meowtch'
+
+        let subject = self.parse_expression()?;
+        let span = self.span_from(start);
+        self.expect_brace("{")?;
+
+        let mut arms = Vec::new();
+        while !self.check(TokenType::Brace, "}") {
+            let pattern = self.parse_pattern()?;
 
-fn parse_variable_declaration(tokens: &[Token], current: &mut usize) -> ASTNode {
-    *current += 1;
+            let guard = if self.check_type(TokenType::If) {
+                self.current += 1;
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
+
+            let arrow = self.peek()?;
+            if arrow.token_type != TokenType::Arrow {
+                return Err(ParseError::Expected {
+                    expected: "'=>'".to_string(),
+                    found: arrow.token_type.clone(),
+                    span: arrow.span,
+                });
+            }
+            self.current += 1;
+
+            let body = if !statement {
+                MatchBody::Expression(Box::new(self.parse_expression()?))
+            } else if self.check(TokenType::Brace, "{") {
+                MatchBody::Block(self.parse_block()?)
+            } else {
+                MatchBody::Block(vec![self.walk()?])
+            };
 
-    let name_token = &tokens[*current];
-    if name_token.token_type != TokenType::Identifier {
-        panic!("Expected variable name after nyan");
+            arms.push(MatchArm { pattern, guard, body });
+
+            if self.check_type(TokenType::Comma) {
+                self.current += 1;
+            }
+        }
+        self.expect_brace("}")?;
+
+        Ok(ASTBuilder::create_match_node(subject, arms, span))
     }
 
-    let name = name_token.value.clone();
-    *current += 1;
+    /// Parses a pattern: `_`, a name to bind, a number, string or bool literal,
+    /// or `Enum.Variant` optionally followed by patterns for the values it carries.
+    fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let start = self.current;
+        let token = self.peek()?;
 
-    expect_token_type(tokens, current, TokenType::Equal);
-    let value = parse_expression(tokens, current);
-    ASTBuilder::create_variable_declaration_node(name, value)
-}
+        match token.token_type {
+            TokenType::Number | TokenType::String | TokenType::Bool | TokenType::Nil => {
+                Ok(Pattern::Literal(Box::new(self.parse_primary_expression()?)))
+            }
+            // A negative number, like `-1`
+            TokenType::Operator
+                if token.value == "-"
+                    && self.tokens.get(self.current + 1).is_some_and(|t| t.token_type == TokenType::Number) =>
+            {
+                Ok(Pattern::Literal(Box::new(self.parse_unary_expression()?)))
+            }
+            TokenType::Identifier if token.value == "_" => {
+                self.current += 1;
+                Ok(Pattern::Wildcard)
+            }
+            TokenType::Identifier => {
+                self.current += 1;
+                if !self.check_type(TokenType::Dot) {
+                    return Ok(Pattern::Binding(token.value.clone()));
+                }
+                self.current += 1;
+
+                let variant = self.peek()?;
+                if variant.token_type != TokenType::Identifier {
+                    return Err(ParseError::Expected {
+                        expected: "variant name".to_string(),
+                        found: variant.token_type.clone(),
+                        span: variant.span,
+                    });
+                }
+                self.current += 1;
+
+                let fields = if self.check(TokenType::Parenthesis, "(") {
+                    self.current += 1;
+                    let mut fields = Vec::new();
+                    while !self.check(TokenType::Parenthesis, ")") {
+                        fields.push(self.parse_pattern()?);
+                        if !self.check_type(TokenType::Comma) {
+                            break;
+                        }
+                        self.current += 1;
+                    }
+                    self.expect_parenthesis(")")?;
+                    Some(fields)
+                } else {
+                    None
+                };
+
+                Ok(Pattern::Variant {
+                    enum_name: token.value.clone(),
+                    variant: variant.value.clone(),
+                    fields,
+                    span: self.span_from(start),
+                })
+            }
+            _ => Err(ParseError::Expected {
+                expected: "pattern".to_string(),
+                found: token.token_type.clone(),
+                span: token.span,
+            }),
+        }
+    }
+
+    /// Parses an anonymous `sparkle(params) { ... }` used as a value.
+    fn parse_lambda(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip 'sparkle'
+
+        let params = self.parse_params()?;
+        let header_span = self.span_from(start);
+        let body = self.parse_function_body()?;
+
+        Ok(ASTBuilder::create_lambda_node(params, body, header_span))
+    }
+
+    fn parse_params(&mut self) -> Result<Vec<ASTNode>, ParseError> {
+        self.expect_parenthesis("(")?;
+
+        let mut params = Vec::new();
+        while let Some(token) = self.tokens.get(self.current) {
+            if token.token_type == TokenType::Parenthesis && token.value == ")" {
+                break;
+            }
+
+            if token.token_type == TokenType::OwO || token.token_type == TokenType::Print || token.token_type == TokenType::FunctionDef || token.token_type == TokenType::VarDecl || token.token_type == TokenType::ConstDecl || token.token_type == TokenType::Return || token.token_type == TokenType::KindOf || token.token_type == TokenType::If || token.token_type == TokenType::ElseIf || token.token_type == TokenType::Else || token.token_type == TokenType::While || token.token_type == TokenType::Break || token.token_type == TokenType::Continue || token.token_type == TokenType::For || token.token_type == TokenType::In || token.token_type == TokenType::Step || token.token_type == TokenType::Record || token.token_type == TokenType::Class || token.token_type == TokenType::SelfRef || token.token_type == TokenType::Super || token.token_type == TokenType::Enum || token.token_type == TokenType::Match || token.token_type == TokenType::Nil {
+                return Err(ParseError::KeywordAsParameter { span: token.span });
+            } 
+            
+            if token.token_type == TokenType::Identifier {
+                let param_name = token.value.clone();
+                params.push(ASTNode::VariableDeclaration(VariableDeclarationNode {
+                    name: param_name,
+                    value: Box::new(ASTNode::None), // Placeholder value
+                    constant: false,
+                    doc: None,
+                    span: token.span,
+                }));
+                self.current += 1;
+            } else if token.token_type == TokenType::Comma {
+                self.current += 1; // Skip comma and continue to next parameter
+            } else {
+                return Err(ParseError::Expected {
+                    expected: "parameter name".to_string(),
+                    found: token.token_type.clone(),
+                    span: token.span,
+                });
+            }
+        }
+
+        self.expect_parenthesis(")")?;
+        Ok(params)
+    }
+
+    fn parse_function_body(&mut self) -> Result<Vec<ASTNode>, ParseError> {
+        // Loops outside the function can't be broken out of from inside it
+        let outer_loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        self.function_depth += 1;
+        let body = self.parse_block();
+        self.function_depth -= 1;
+        self.loop_depth = outer_loop_depth;
+        body
+    }
+
+    fn parse_return(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        if self.function_depth == 0 {
+            return Err(ParseError::ReturnOutsideFunction {
+                span: self.tokens[start].span,
+            });
+        }
+
+        self.current += 1; // Skip 'bringback'
+        // A bare `bringback` ends its line or its block
+        let bare = !self.on_previous_line() || self.check(TokenType::Brace, "}");
+        let value = if bare { None } else { Some(self.parse_expression()?) };
+        Ok(ASTBuilder::create_return_node(value, self.span_from(start)))
+    }
+
+    /// Parses `nani condition { ... }` along with its `ownani` and `otherwise` branches.
+    fn parse_if(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip 'nani' or 'ownani'
+
+        let condition = self.parse_expression()?;
+        let span = self.span_from(start);
+        let body = self.parse_block()?;
+
+        let else_body = if self.check_type(TokenType::ElseIf) {
+            Some(vec![self.parse_if()?])
+        } else if self.check_type(TokenType::Else) {
+            self.current += 1;
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+
+        Ok(ASTBuilder::create_if_node(condition, body, else_body, span))
+    }
+
+    fn parse_while(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip 'whiwe'
+
+        let condition = self.parse_expression()?;
+        let span = self.span_from(start);
+
+        self.loop_depth += 1;
+        let body = self.parse_block();
+        self.loop_depth -= 1;
+
+        Ok(ASTBuilder::create_while_node(condition, body?, span))
+    }
+
+    fn parse_for(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip 'fow'
+
+        let name_token = self.peek()?;
+        if name_token.token_type != TokenType::Identifier {
+            return Err(ParseError::MissingName {
+                after: "fow",
+                span: name_token.span,
+            });
+        }
+        self.current += 1;
+
+        self.expect_token_type(TokenType::In)?;
+        let iterable = self.parse_range_or_expression()?;
+        let span = self.span_from(start);
+
+        self.loop_depth += 1;
+        let body = self.parse_block();
+        self.loop_depth -= 1;
+
+        Ok(ASTBuilder::create_for_node(name_token.value.clone(), iterable, body?, span))
+    }
+
+    /// Parses an expression, or a `start..end by step` range when a `..` follows it.
+    fn parse_range_or_expression(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        let expr = self.parse_expression()?;
+        if !self.check_type(TokenType::Range) {
+            return Ok(expr);
+        }
+
+        self.current += 1; // Skip '..'
+        let end = self.parse_expression()?;
+        let step = if self.check_type(TokenType::Step) {
+            self.current += 1;
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
+        Ok(ASTBuilder::create_range_node(expr, end, step, self.span_from(start)))
+    }
+
+    /// Parses `bweak` and `continyu`, which are only allowed inside a loop.
+    fn parse_loop_jump(&mut self) -> Result<ASTNode, ParseError> {
+        let token = &self.tokens[self.current];
+        if self.loop_depth == 0 {
+            return Err(ParseError::JumpOutsideLoop {
+                keyword: token.value.clone(),
+                span: token.span,
+            });
+        }
+
+        self.current += 1;
+        if token.token_type == TokenType::Break {
+            Ok(ASTBuilder::create_break_node(token.span))
+        } else {
+            Ok(ASTBuilder::create_continue_node(token.span))
+        }
+    }
+
+    /// Parses statements between braces. Broken statements are recorded and skipped
+    /// so the rest of the block still makes it into the tree.
+    fn parse_block(&mut self) -> Result<Vec<ASTNode>, ParseError> {
+        self.expect_brace("{")?;
+
+        let mut body = Vec::new();
+        while self.tokens.get(self.current).is_some_and(|t| {
+            t.token_type != TokenType::Brace || t.value != "}"
+        }) {
+            let statement_start = self.current;
+            match self.walk() {
+                Ok(node) => body.push(node),
+                Err(error) => body.push(self.recover(error, statement_start)),
+            }
+        }
+
+        self.expect_brace("}")?;
+        Ok(body)
+    }
 
-fn parse_identifier(tokens: &[Token], current: &mut usize) -> ASTNode {
-    let name = tokens[*current].value.clone();
+    /// Parses a `{ key: value }` map literal. Blocks never start where an expression is expected
+    /// (they are read by `parse_block`), so a '{' seen here can only be a map.
+    fn parse_map(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        self.current += 1; // Skip '{'
 
-    if tokens.get(*current + 1).map_or(false, |t| {
-        t.token_type == TokenType::Parenthesis && t.value == "("
-    }) {
-        *current += 2; // Skip the identifier and '('
+        let mut entries = Vec::new();
+        while !self.check(TokenType::Brace, "}") {
+            let key = self.parse_expression()?;
+            self.expect_token_type(TokenType::Colon)?;
+            let value = self.parse_expression()?;
+            entries.push((key, value));
 
+            if !self.check_type(TokenType::Comma) {
+                break;
+            }
+            self.current += 1;
+        }
+
+        self.expect_brace("}")?;
+        Ok(ASTBuilder::create_map_node(entries, self.span_from(start)))
+    }
+
+    /// Parses an expression used as a statement, like a call, or an assignment to a variable or an item.
+    fn parse_expression_statement(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        let expr = self.parse_expression()?;
+
+        let operator = match self.tokens.get(self.current) {
+            Some(token) if token.token_type == TokenType::Equal => None,
+            Some(token) if token.token_type == TokenType::CompoundAssign => {
+                Some(token.value.trim_end_matches('=').to_string())
+            }
+            _ => return Ok(expr),
+        };
+
+        let assignable = match &expr {
+            ASTNode::VariableReference(var) => var.name != "sewf",
+            ASTNode::Index(_) | ASTNode::MemberAccess(_) => true,
+            _ => false,
+        };
+        if !assignable {
+            return Err(ParseError::InvalidAssignmentTarget { span: expr.span() });
+        }
+
+        self.current += 1; // Skip '=' or the compound operator
+        let value = self.parse_expression()?;
+        Ok(ASTBuilder::create_assignment_node(expr, operator, value, self.span_from(start)))
+    }
+
+    /// Parses `nyan name = value`, or `fowever name = value` for a constant.
+    fn parse_variable_declaration(&mut self) -> Result<ASTNode, ParseError> {
+        let start = self.current;
+        let constant = self.tokens[start].token_type == TokenType::ConstDecl;
+        self.current += 1;
+
+        let name_token = self.peek()?;
+        if name_token.token_type != TokenType::Identifier {
+            return Err(ParseError::MissingName {
+                after: if constant { "fowever" } else { "nyan" },
+                span: name_token.span,
+            });
+        }
+
+        let name = name_token.value.clone();
+        self.current += 1;
+
+        self.expect_token_type(TokenType::Equal)?;
+        let value = self.parse_expression()?;
+        Ok(ASTBuilder::create_variable_declaration_node(name, value, constant, self.span_from(start)))
+    }
+
+    fn parse_identifier(&mut self) -> Result<ASTNode, ParseError> {
+        let token = &self.tokens[self.current];
+        self.current += 1;
+        Ok(ASTBuilder::create_variable_reference_node(token.value.clone(), token.span))
+    }
+
+    /// Parses the arguments of a call, the opening '(' being already consumed.
+    fn parse_arguments(&mut self) -> Result<Vec<ASTNode>, ParseError> {
         let mut arguments = Vec::new();
 
         // Parse arguments separated by commas
-        while let Some(token) = tokens.get(*current) {
+        while let Some(token) = self.tokens.get(self.current) {
             if token.token_type == TokenType::Parenthesis && token.value == ")" {
                 break;
             }
 
             if token.token_type != TokenType::Comma {
                 // Only parse expression if not a comma
-                arguments.push(parse_expression(tokens, current));
+                arguments.push(self.parse_expression()?);
             }
 
             // Skip commas
             if token.token_type == TokenType::Comma {
-                *current += 1;
+                self.current += 1;
             }
         }
 
-        expect_parenthesis(tokens, current, ")");
-        ASTBuilder::create_function_call_node(name, arguments)
-    } else {
-        *current += 1;
-        ASTBuilder::create_variable_reference_node(name)
+        self.expect_parenthesis(")")?;
+        Ok(arguments)
+    }
+
+    // === Error Recovery ===
+
+    /// Records `error` and skips ahead to the next statement boundary.
+    /// Returns the placeholder node standing in for the broken statement.
+    fn recover(&mut self, error: ParseError, start: usize) -> ASTNode {
+        let span = error.span();
+        self.errors.push(error);
+
+        // Always make progress, even if the statement failed on its first token
+        if self.current == start {
+            self.current += 1;
+        }
+        self.synchronize();
+
+        ASTBuilder::create_error_node(span)
+    }
+
+    /// Skips tokens until one that can start a statement, or the '}' closing the current block.
+    /// A block opened by the broken statement (e.g. a function body) is skipped as a whole.
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+
+        while let Some(token) = self.tokens.get(self.current) {
+            match token.token_type {
+                TokenType::Brace if token.value == "{" => depth += 1,
+                TokenType::Brace if token.value == "}" => {
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
+                }
+                TokenType::VarDecl
+                | TokenType::ConstDecl
+                | TokenType::FunctionDef
+                | TokenType::Record
+                | TokenType::Class
+                | TokenType::Enum
+                | TokenType::Match
+                | TokenType::Print
+                | TokenType::OwO
+                | TokenType::Return
+                | TokenType::If
+                | TokenType::While
+                | TokenType::For
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::DocComment if depth == 0 => return,
+                _ => {}
+            }
+            self.current += 1;
+        }
+    }
+
+    // === Token Expectations ===
+
+    fn expect_token_type(&mut self, expected: TokenType) -> Result<(), ParseError> {
+        let token = self.peek()?;
+        if token.token_type != expected {
+            return Err(ParseError::Expected {
+                expected: format!("token type {:?}", expected),
+                found: token.token_type.clone(),
+                span: token.span,
+            });
+        }
+        self.current += 1;
+        Ok(())
+    }
+
+    fn expect_parenthesis(&mut self, expected: &str) -> Result<(), ParseError> {
+        let token = self.peek()?;
+        if token.token_type != TokenType::Parenthesis || token.value != expected {
+            return Err(ParseError::Expected {
+                expected: format!("parenthesis '{}'", expected),
+                found: token.token_type.clone(),
+                span: token.span,
+            });
+        }
+        self.current += 1;
+        Ok(())
     }
-}
 
-// === Token Expectations ===
+    fn expect_brace(&mut self, expected: &str) -> Result<(), ParseError> {
+        let token = self.peek()?;
+        if token.token_type != TokenType::Brace || token.value != expected {
+            return Err(ParseError::Expected {
+                expected: format!("brace '{}'", expected),
+                found: token.token_type.clone(),
+                span: token.span,
+            });
+        }
+        self.current += 1;
+        Ok(())
+    }
+
+    fn expect_bracket(&mut self, expected: &str) -> Result<(), ParseError> {
+        let token = self.peek()?;
+        if token.token_type != TokenType::Bracket || token.value != expected {
+            return Err(ParseError::Expected {
+                expected: format!("bracket '{}'", expected),
+                found: token.token_type.clone(),
+                span: token.span,
+            });
+        }
+        self.current += 1;
+        Ok(())
+    }
+
+    // === Helpers ===
+
+    /// Returns the current token, or an end of file error pointing right after the last token.
+    fn peek(&self) -> Result<&'a Token, ParseError> {
+        let tokens = self.tokens;
+        tokens.get(self.current).ok_or_else(|| {
+            let span = tokens.last().map_or(Span::new(1, 1, 1), |last| {
+                Span::new(last.span.line, last.span.column + last.span.length, 1)
+            });
+            ParseError::UnexpectedEndOfFile { span }
+        })
+    }
+
+    /// Consumes the current token if it is one of the given operators.
+    fn match_operator(&mut self, operators: &[&str]) -> Option<String> {
+        let token = self.tokens.get(self.current)?;
+        if token.token_type == TokenType::Operator && operators.contains(&token.value.as_str()) {
+            self.current += 1;
+            return Some(token.value.clone());
+        }
+        None
+    }
 
-fn expect_token_type(tokens: &[Token], current: &mut usize, expected: TokenType) {
-    let token = &tokens[*current];
-    if token.token_type != expected {
-        panic!(
-            "Expected token type {:?}, but got {:?}",
-            expected, token.token_type
-        );
+    fn check_type(&self, token_type: TokenType) -> bool {
+        self.tokens
+            .get(self.current)
+            .is_some_and(|t| t.token_type == token_type)
+    }
+
+    fn check(&self, token_type: TokenType, value: &str) -> bool {
+        self.tokens
+            .get(self.current)
+            .is_some_and(|t| t.token_type == token_type && t.value == value)
+    }
+
+    /// Whether the current token sits on the same line as the previous one.
+    fn on_previous_line(&self) -> bool {
+        self.current > 0
+            && self.tokens.get(self.current).is_some_and(|t| {
+                t.span.line == self.tokens[self.current - 1].span.line
+            })
+    }
+
+    /// Span going from the token at `start` to the last consumed token.
+    fn span_from(&self, start: usize) -> Span {
+        self.tokens[start].span.to(self.tokens[self.current - 1].span)
     }
-    *current += 1;
 }
 
-fn expect_parenthesis(tokens: &[Token], current: &mut usize, expected: &str) {
-    let token = &tokens[*current];
-    if token.token_type != TokenType::Parenthesis || token.value != expected {
-        panic!("Expected parenthesis '{}'", expected);
+// === Number Literals ===
+
+/// Reads a number literal: `42`, `1_000_000`, `0xFF`, `0b1010`, `0o17`, `3.14` or `1.5e-3`.
+/// Literals with a dot or an exponent are floats, the others are ints (of any size).
+/// Returns `None` for malformed ones like `1.2.3`, `0xZ` or `1__000`.
+fn number_value(literal: &str) -> Option<Numeric> {
+    let (radix, digits) = match literal.get(..2) {
+        Some("0x" | "0X") => (16, &literal[2..]),
+        Some("0b" | "0B") => (2, &literal[2..]),
+        Some("0o" | "0O") => (8, &literal[2..]),
+        _ => (10, literal),
+    };
+
+    // A '_' has to sit between two digits
+    let is_digit = |c: char| if radix == 10 { c.is_ascii_digit() } else { c.is_ascii_hexdigit() };
+    let chars: Vec<char> = digits.chars().collect();
+    let separators_fit = chars.iter().enumerate().all(|(i, c)| {
+        *c != '_' || (i > 0 && is_digit(chars[i - 1]) && chars.get(i + 1).is_some_and(|next| is_digit(*next)))
+    });
+    if !separators_fit {
+        return None;
+    }
+
+    let digits: String = chars.into_iter().filter(|c| *c != '_').collect();
+    // A trailing `d` asks for an exact decimal instead of a float
+    if radix == 10
+        && let Some(decimal) = digits.strip_suffix('d')
+    {
+        return Decimal::parse(decimal).map(Numeric::Decimal);
     }
-    *current += 1;
+    if radix == 10 && digits.contains(['.', 'e', 'E']) {
+        return digits.parse::<f64>().ok().filter(|n| n.is_finite()).map(Numeric::Float);
+    }
+
+    // Signs are not digits, even if `parse_bytes` accepts them
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let value = BigInt::parse_bytes(digits.as_bytes(), radix)?;
+    Some(match i64::try_from(&value) {
+        Ok(small) => Numeric::Int(small),
+        Err(_) => Numeric::BigInt(value),
+    })
+}
+
+// === Doc Comments ===
+
+/// Drops the `##` lines that aren't right above a declaration, those are plain comments.
+fn without_dangling_doc_comments(tokens: &[Token]) -> Vec<Token> {
+    let documents_declaration = |i: usize| {
+        tokens[i..].iter().find(|t| t.token_type != TokenType::DocComment).is_some_and(|t| {
+            matches!(
+                t.token_type,
+                TokenType::FunctionDef
+                    | TokenType::Record
+                    | TokenType::Class
+                    | TokenType::Enum
+                    | TokenType::VarDecl
+                    | TokenType::ConstDecl
+            )
+        })
+    };
+
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, t)| t.token_type != TokenType::DocComment || documents_declaration(*i))
+        .map(|(_, t)| t.clone())
+        .collect()
 }
 
-fn expect_brace(tokens: &[Token], current: &mut usize, expected: &str) {
-    let token = &tokens[*current];
-    if token.token_type != TokenType::Brace || token.value != expected {
-        panic!("Expected brace '{}'", expected);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The literal's value with the kind it was read as: `int 42`, `float 0.5`...
+    fn read(literal: &str) -> Option<String> {
+        Some(match number_value(literal)? {
+            Numeric::Int(n) => format!("int {}", n),
+            Numeric::BigInt(n) => format!("big {}", n),
+            Numeric::Float(n) => format!("float {}", n),
+            Numeric::Decimal(n) => format!("decimal {}", n),
+        })
+    }
+
+    #[test]
+    fn ints_in_every_base() {
+        assert_eq!(read("42").as_deref(), Some("int 42"));
+        assert_eq!(read("0xFF").as_deref(), Some("int 255"));
+        assert_eq!(read("0Xff").as_deref(), Some("int 255"));
+        assert_eq!(read("0b1010").as_deref(), Some("int 10"));
+        assert_eq!(read("0o17").as_deref(), Some("int 15"));
+        assert_eq!(read("007").as_deref(), Some("int 7"));
+    }
+
+    #[test]
+    fn ints_past_64_bits_are_big() {
+        assert_eq!(read("9223372036854775807").as_deref(), Some("int 9223372036854775807"));
+        assert_eq!(read("9223372036854775808").as_deref(), Some("big 9223372036854775808"));
+        assert_eq!(read("0xFFFF_FFFF_FFFF_FFFF").as_deref(), Some("big 18446744073709551615"));
+    }
+
+    #[test]
+    fn separators_sit_between_digits() {
+        assert_eq!(read("1_000_000").as_deref(), Some("int 1000000"));
+        assert_eq!(read("0b1111_0000").as_deref(), Some("int 240"));
+        assert_eq!(read("1_0.2_5").as_deref(), Some("float 10.25"));
+        for literal in ["1__000", "1000_", "0x_FF", "1_.5", "1._5"] {
+            assert_eq!(read(literal), None, "{literal}");
+        }
+    }
+
+    #[test]
+    fn floats_and_decimals() {
+        assert_eq!(read("3.14").as_deref(), Some("float 3.14"));
+        assert_eq!(read("1e3").as_deref(), Some("float 1000"));
+        assert_eq!(read("1.5e-3").as_deref(), Some("float 0.0015"));
+        assert_eq!(read("0.1d").as_deref(), Some("decimal 0.1"));
+        assert_eq!(read("2d").as_deref(), Some("decimal 2.0"));
+        assert_eq!(read("1.50e1d").as_deref(), Some("decimal 15.0"));
+    }
+
+    #[test]
+    fn malformed_literals() {
+        for literal in ["1.2.3", "0xZ", "0x", "0b102", "0o8", "1e", "1e999", "0x1.5", "1.2.3d"] {
+            assert_eq!(read(literal), None, "{literal}");
+        }
     }
-    *current += 1;
 }
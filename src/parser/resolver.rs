@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use crate::parser::ast::*;
+use crate::parser::errors::ParseError;
+use crate::utils::diagnostics::Span;
+
+/// Looks for mistakes that can be seen without running the script: reassigning a `fowever`
+/// constant, declaring a name twice in one scope when one of the two is a constant, patterns
+/// that don't fit their `flavor`, and `meowtch`es that forget some of its variants.
+///
+/// Only mistakes that happen whenever the code runs are reported. A declaration inside a
+/// `nani` or a loop may not run, so it never causes an error here; the interpreter checks
+/// those cases again when it gets to them.
+pub fn resolve(ast: &[ASTNode]) -> Vec<ParseError> {
+    let mut resolver = Resolver {
+        scopes: vec![Scope::default()],
+        errors: Vec::new(),
+    };
+    resolver.resolve_block(ast);
+    resolver.end_scope();
+
+    // Function bodies are checked after their surroundings, put everything back in file order
+    resolver.errors.sort_by_key(|error| (error.span().line, error.span().column));
+    resolver.errors
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    constant: bool,
+    /// `false` when the declaration sits in a branch or a loop, which may not run.
+    definite: bool,
+}
+
+/// Mirrors the interpreter: one scope for the globals, one per function, one per `fow` loop and
+/// one per `meowtch` arm, other blocks share the scope around them.
+#[derive(Default)]
+struct Scope<'a> {
+    bindings: HashMap<String, Binding>,
+    /// Parameters and bodies of the functions created in this scope. They are checked once the
+    /// scope ends, so they can see every name around them, like they do when they get called.
+    functions: Vec<(&'a [ASTNode], &'a [ASTNode])>,
+    /// The `flavor`s declared in this scope, to check the patterns using them.
+    enums: HashMap<String, &'a EnumDeclarationNode>,
+}
+
+struct Resolver<'a> {
+    scopes: Vec<Scope<'a>>,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve_block(&mut self, body: &'a [ASTNode]) {
+        // The interpreter hoists functions, record types and enums, so their names are taken from the start of the block
+        for stmt in body {
+            match stmt {
+                ASTNode::FunctionDeclaration(func) => self.declare(&func.name, false, func.span),
+                ASTNode::RecordDeclaration(record) => self.declare(&record.name, false, record.span),
+                ASTNode::EnumDeclaration(enum_node) => {
+                    self.declare(&enum_node.name, false, enum_node.span);
+                    self.current_scope().enums.insert(enum_node.name.clone(), enum_node);
+                }
+                _ => {}
+            }
+        }
+
+        for stmt in body {
+            self.resolve_statement(stmt);
+        }
+    }
+
+    fn resolve_statement(&mut self, node: &'a ASTNode) {
+        match node {
+            ASTNode::VariableDeclaration(decl) => {
+                self.resolve_expression(&decl.value);
+                self.declare(&decl.name, decl.constant, decl.span);
+            }
+
+            ASTNode::Assignment(assignment) => {
+                self.resolve_expression(&assignment.target);
+                self.resolve_expression(&assignment.value);
+                if let ASTNode::VariableReference(var) = assignment.target.as_ref()
+                    && self.lookup(&var.name).is_some_and(|b| b.constant && b.definite)
+                {
+                    self.errors.push(ParseError::ConstantAssignment {
+                        name: var.name.clone(),
+                        span: assignment.span,
+                    });
+                }
+            }
+
+            ASTNode::FunctionDeclaration(func) => self.defer_function(&func.params, &func.body),
+
+            ASTNode::ClassDeclaration(class) => {
+                for method in &class.methods {
+                    self.defer_function(&method.params, &method.body);
+                }
+                self.declare(&class.name, false, class.span);
+            }
+
+            ASTNode::If(if_node) => {
+                self.resolve_expression(&if_node.condition);
+                self.resolve_branch(|resolver| resolver.resolve_block(&if_node.body));
+                if let Some(else_body) = &if_node.else_body {
+                    self.resolve_branch(|resolver| resolver.resolve_block(else_body));
+                }
+            }
+
+            ASTNode::While(while_node) => {
+                self.resolve_expression(&while_node.condition);
+                self.resolve_branch(|resolver| resolver.resolve_block(&while_node.body));
+            }
+
+            ASTNode::For(for_node) => {
+                self.resolve_expression(&for_node.iterable);
+                let mut scope = Scope::default();
+                scope.bindings.insert(for_node.variable.clone(), Binding { constant: false, definite: true });
+                self.scopes.push(scope);
+                self.resolve_block(&for_node.body);
+                self.end_scope();
+            }
+
+            ASTNode::Print(p) => self.resolve_expression(&p.expression),
+            ASTNode::OwO(p) => self.resolve_expression(&p.expression),
+            ASTNode::Return(ret) => {
+                if let Some(value) = &ret.value {
+                    self.resolve_expression(value);
+                }
+            }
+
+            _ => self.resolve_expression(node),
+        }
+    }
+
+    /// Expressions can't declare anything, but they can hold lambdas whose bodies need checking.
+    fn resolve_expression(&mut self, node: &'a ASTNode) {
+        match node {
+            ASTNode::Lambda(lambda) => self.defer_function(&lambda.params, &lambda.body),
+            ASTNode::FunctionCall(call) => {
+                self.resolve_expression(&call.callee);
+                for arg in &call.arguments {
+                    self.resolve_expression(arg);
+                }
+            }
+            ASTNode::BinaryExpression(expr) => {
+                self.resolve_expression(&expr.left);
+                self.resolve_expression(&expr.right);
+            }
+            ASTNode::LogicalExpression(expr) => {
+                self.resolve_expression(&expr.left);
+                self.resolve_expression(&expr.right);
+            }
+            ASTNode::UnaryExpression(expr) => self.resolve_expression(&expr.operand),
+            ASTNode::KindOf(k) => self.resolve_expression(&k.expression),
+            ASTNode::Interpolation(interpolation) => {
+                for part in &interpolation.parts {
+                    self.resolve_expression(part);
+                }
+            }
+            ASTNode::List(list) => {
+                for element in &list.elements {
+                    self.resolve_expression(element);
+                }
+            }
+            ASTNode::Map(map) => {
+                for (key, value) in &map.entries {
+                    self.resolve_expression(key);
+                    self.resolve_expression(value);
+                }
+            }
+            ASTNode::MemberAccess(access) => self.resolve_expression(&access.target),
+            ASTNode::Index(index) => {
+                self.resolve_expression(&index.target);
+                self.resolve_expression(&index.index);
+            }
+            ASTNode::Slice(slice) => {
+                self.resolve_expression(&slice.target);
+                for bound in [&slice.start, &slice.end].into_iter().flatten() {
+                    self.resolve_expression(bound);
+                }
+            }
+            ASTNode::Range(range) => {
+                self.resolve_expression(&range.start);
+                self.resolve_expression(&range.end);
+                if let Some(step) = &range.step {
+                    self.resolve_expression(step);
+                }
+            }
+            ASTNode::Match(match_node) => self.resolve_match(match_node),
+            _ => {}
+        }
+    }
+
+    /// Checks the arms of a `meowtch`, then whether they cover every variant of the `flavor` they match on.
+    fn resolve_match(&mut self, match_node: &'a MatchNode) {
+        self.resolve_expression(&match_node.subject);
+
+        for arm in &match_node.arms {
+            self.check_pattern(&arm.pattern);
+            let mut scope = Scope::default();
+            for name in arm.pattern.bindings() {
+                scope.bindings.insert(name.to_string(), Binding { constant: false, definite: true });
+            }
+            self.scopes.push(scope);
+
+            if let Some(guard) = &arm.guard {
+                self.resolve_expression(guard);
+            }
+            match &arm.body {
+                MatchBody::Block(body) => self.resolve_block(body),
+                MatchBody::Expression(expr) => self.resolve_expression(expr),
+            }
+            self.end_scope();
+        }
+
+        // Only matches on a known enum can be checked, and a guarded arm may always be skipped
+        let unguarded = || match_node.arms.iter().filter(|arm| arm.guard.is_none());
+        if unguarded().any(|arm| arm.pattern.is_irrefutable()) {
+            return;
+        }
+        let Some(enum_name) = match_node.arms.iter().find_map(|arm| match &arm.pattern {
+            Pattern::Variant { enum_name, .. } => Some(enum_name),
+            _ => None,
+        }) else {
+            return;
+        };
+        let Some(enum_node) = self.lookup_enum(enum_name) else {
+            return;
+        };
+
+        let covered: Vec<&str> = unguarded()
+            .filter_map(|arm| match &arm.pattern {
+                Pattern::Variant { enum_name: name, variant, fields, .. }
+                    if name == enum_name
+                        && fields.iter().flatten().all(Pattern::is_irrefutable) =>
+                {
+                    Some(variant.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        let missing: Vec<String> = enum_node
+            .variants
+            .iter()
+            .filter(|variant| !covered.contains(&variant.name.as_str()))
+            .map(|variant| variant.name.clone())
+            .collect();
+
+        if !missing.is_empty() {
+            self.errors.push(ParseError::NonExhaustiveMatch {
+                enum_name: enum_name.clone(),
+                missing,
+                span: match_node.span,
+            });
+        }
+    }
+
+    /// Checks that the variants named in a pattern exist and get as many patterns as they carry values.
+    fn check_pattern(&mut self, pattern: &Pattern) {
+        let Pattern::Variant { enum_name, variant, fields, span } = pattern else {
+            return;
+        };
+
+        if let Some(enum_node) = self.lookup_enum(enum_name) {
+            match enum_node.variants.iter().find(|v| &v.name == variant) {
+                None => self.errors.push(ParseError::UnknownVariant {
+                    enum_name: enum_name.clone(),
+                    variant: variant.clone(),
+                    span: *span,
+                }),
+                Some(declared) => {
+                    if let Some(fields) = fields
+                        && fields.len() != declared.fields.len()
+                    {
+                        self.errors.push(ParseError::PatternArity {
+                            variant: format!("{}.{}", enum_name, variant),
+                            expected: declared.fields.len(),
+                            found: fields.len(),
+                            span: *span,
+                        });
+                    }
+                }
+            }
+        }
+
+        for field in fields.iter().flatten() {
+            self.check_pattern(field);
+        }
+    }
+
+    /// Checks code that may run any number of times. What it declares is still known afterwards,
+    /// but only as something that might exist.
+    fn resolve_branch(&mut self, resolve: impl FnOnce(&mut Self)) {
+        let before = self.bindings().clone();
+        resolve(self);
+
+        let after = std::mem::replace(self.bindings(), before);
+        for (name, binding) in after {
+            let bindings = self.bindings();
+            if bindings.get(&name).is_some_and(|b| b.definite) {
+                continue;
+            }
+            bindings.insert(name, Binding { definite: false, ..binding });
+        }
+    }
+
+    fn declare(&mut self, name: &str, constant: bool, span: Span) {
+        // Whatever the name held before, it is no longer that enum
+        self.current_scope().enums.remove(name);
+
+        if let Some(existing) = self.bindings().get(name).copied().filter(|b| b.definite) {
+            if existing.constant {
+                self.errors.push(ParseError::ConstantRedeclaration { name: name.to_string(), span });
+            } else if constant {
+                self.errors.push(ParseError::AlreadyDeclared { name: name.to_string(), span });
+            }
+            return;
+        }
+        self.bindings().insert(name.to_string(), Binding { constant, definite: true });
+    }
+
+    fn defer_function(&mut self, params: &'a [ASTNode], body: &'a [ASTNode]) {
+        self.current_scope().functions.push((params, body));
+    }
+
+    /// Checks the functions created in the current scope, then leaves it.
+    fn end_scope(&mut self) {
+        // Checking a body may defer more functions, those go into the body's own scope
+        let functions = std::mem::take(&mut self.current_scope().functions);
+        for (params, body) in functions {
+            let mut scope = Scope::default();
+            for param in params {
+                if let ASTNode::VariableDeclaration(param) = param {
+                    scope.bindings.insert(param.name.clone(), Binding { constant: false, definite: true });
+                }
+            }
+
+            self.scopes.push(scope);
+            self.resolve_block(body);
+            self.end_scope();
+        }
+        self.scopes.pop();
+    }
+
+    /// Finds the binding `name` refers to, looking from the innermost scope outwards.
+    fn lookup(&self, name: &str) -> Option<Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.bindings.get(name).copied())
+    }
+
+    /// Finds the enum `name` refers to, unless something else has taken that name in between.
+    fn lookup_enum(&self, name: &str) -> Option<&'a EnumDeclarationNode> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(enum_node) = scope.enums.get(name) {
+                return Some(enum_node);
+            }
+            if scope.bindings.contains_key(name) {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn current_scope(&mut self) -> &mut Scope<'a> {
+        self.scopes.last_mut().expect("the global scope is only left at the end")
+    }
+
+    fn bindings(&mut self) -> &mut HashMap<String, Binding> {
+        &mut self.current_scope().bindings
+    }
+}
@@ -0,0 +1,298 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::{BigInt, Sign};
+use num_traits::{Signed, Zero};
+
+/// Digits kept after the dot when a division doesn't come out even.
+const DIVISION_PLACES: u32 = 28;
+
+/// Largest exponent accepted when reading `1e10d`, so a typo can't ask for a billion zeros.
+const MAX_EXPONENT: i64 = 4096;
+
+/// An exact decimal number: `digits × 10^-scale`. Trailing zeros are always dropped,
+/// so each number is stored one way only and `0.10d == 0.1d` comes for free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decimal {
+    digits: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    fn new(mut digits: BigInt, mut scale: u32) -> Decimal {
+        let ten = BigInt::from(10);
+        while scale > 0 && (&digits % &ten).is_zero() {
+            digits /= &ten;
+            scale -= 1;
+        }
+        Decimal { digits, scale }
+    }
+
+    pub fn from_int(n: BigInt) -> Decimal {
+        Decimal { digits: n, scale: 0 }
+    }
+
+    /// Reads `12`, `-0.25` or `1.5e-3`. Returns `None` for anything else.
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let (mantissa, exponent) = match text.find(['e', 'E']) {
+            Some(i) => (&text[..i], text[i + 1..].parse::<i64>().ok()?),
+            None => (text, 0),
+        };
+        if exponent.abs() > MAX_EXPONENT {
+            return None;
+        }
+
+        let (negative, unsigned) = match mantissa.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+        let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        let all_digits = whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit());
+        if !all_digits || whole.len() + fraction.len() == 0 {
+            return None;
+        }
+
+        let mut digits: BigInt = format!("{}{}", whole, fraction).parse().ok()?;
+        if negative {
+            digits = -digits;
+        }
+
+        let scale = fraction.len() as i64 - exponent;
+        if scale < 0 {
+            return Some(Decimal::new(digits * power_of_ten(-scale as u32), 0));
+        }
+        Some(Decimal::new(digits, scale as u32))
+    }
+
+    /// `self / other`, rounded to 28 digits after the dot with ties going to the even digit.
+    /// Returns `None` when `other` is zero.
+    pub fn checked_div(&self, other: &Decimal) -> Option<Decimal> {
+        if other.digits.is_zero() {
+            return None;
+        }
+
+        // (a × 10^-sa) / (b × 10^-sb) = (a × 10^(P + sb)) / (b × 10^sa) × 10^-P
+        let numerator = &self.digits * power_of_ten(DIVISION_PLACES + other.scale);
+        let denominator = &other.digits * power_of_ten(self.scale);
+        Some(Decimal::new(divide_rounded(&numerator, &denominator), DIVISION_PLACES))
+    }
+
+    /// `self // other` and `self % other`, rounding toward minus infinity like ints do.
+    /// Returns `None` when `other` is zero.
+    pub fn floor_div_rem(&self, other: &Decimal) -> Option<(Decimal, Decimal)> {
+        if other.digits.is_zero() {
+            return None;
+        }
+
+        let (l, r, scale) = self.aligned(other);
+        let (quotient, remainder) = floor_div_rem_big(&l, &r);
+        Some((Decimal::from_int(quotient), Decimal::new(remainder, scale)))
+    }
+
+    /// `self` multiplied by itself `exponent` times, which is always exact.
+    pub fn pow(&self, exponent: u32) -> Decimal {
+        Decimal::new(self.digits.pow(exponent), self.scale * exponent)
+    }
+
+    /// The whole part, dropping what comes after the dot.
+    pub fn trunc(&self) -> BigInt {
+        &self.digits / power_of_ten(self.scale)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn is_whole(&self) -> bool {
+        self.scale == 0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.digits.sign() == Sign::Minus
+    }
+
+    /// Both digit counts brought to the same scale, so they can be added or compared directly.
+    fn aligned(&self, other: &Decimal) -> (BigInt, BigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        (
+            &self.digits * power_of_ten(scale - self.scale),
+            &other.digits * power_of_ten(scale - other.scale),
+            scale,
+        )
+    }
+}
+
+impl Add for &Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: &Decimal) -> Decimal {
+        let (l, r, scale) = self.aligned(other);
+        Decimal::new(l + r, scale)
+    }
+}
+
+impl Sub for &Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: &Decimal) -> Decimal {
+        let (l, r, scale) = self.aligned(other);
+        Decimal::new(l - r, scale)
+    }
+}
+
+impl Mul for &Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: &Decimal) -> Decimal {
+        Decimal::new(&self.digits * &other.digits, self.scale + other.scale)
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal {
+            digits: -&self.digits,
+            scale: self.scale,
+        }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        let (l, r, _) = self.aligned(other);
+        l.cmp(&r)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Shown like floats are: `0.3`, `-12.25`, and `2.0` for whole numbers.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.is_negative() { "-" } else { "" };
+        let digits = self.digits.abs().to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}.0", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
+
+fn power_of_ten(exponent: u32) -> BigInt {
+    BigInt::from(10).pow(exponent)
+}
+
+/// `l // r` and `l % r` on big ints, rounding toward minus infinity so the remainder takes the
+/// sign of `r`. `r` must not be zero.
+pub fn floor_div_rem_big(l: &BigInt, r: &BigInt) -> (BigInt, BigInt) {
+    let (quotient, remainder) = (l / r, l % r);
+    if !remainder.is_zero() && (remainder.sign() == Sign::Minus) != (r.sign() == Sign::Minus) {
+        return (quotient - 1, remainder + r);
+    }
+    (quotient, remainder)
+}
+
+/// `numerator / denominator` rounded to the closest int, ties going to the even one.
+fn divide_rounded(numerator: &BigInt, denominator: &BigInt) -> BigInt {
+    let quotient = numerator / denominator;
+    let twice_remainder = (numerator % denominator).abs() * BigInt::from(2);
+
+    let round_away = match twice_remainder.cmp(&denominator.abs()) {
+        Ordering::Less => false,
+        Ordering::Greater => true,
+        Ordering::Equal => !(&quotient % BigInt::from(2)).is_zero(),
+    };
+    if !round_away {
+        return quotient;
+    }
+
+    // The quotient was cut toward zero, rounding away goes one further from it
+    let negative = (numerator.sign() == Sign::Minus) != (denominator.sign() == Sign::Minus);
+    if negative { quotient - 1 } else { quotient + 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    fn quotient(l: &str, r: &str) -> String {
+        dec(l).checked_div(&dec(r)).unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_drops_trailing_zeros() {
+        assert_eq!(dec("0.10"), dec("0.1"));
+        assert_eq!(dec("1.5e-3").to_string(), "0.0015");
+        assert_eq!(dec("-12e2").to_string(), "-1200.0");
+        assert_eq!(dec("+7").to_string(), "7.0");
+    }
+
+    #[test]
+    fn parse_rejects_malformed_text() {
+        for text in ["", ".", "1.2.3", "--1", "1e", "0x10", "1e5000"] {
+            assert_eq!(Decimal::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn division_keeps_28_places() {
+        assert_eq!(quotient("1", "3"), "0.3333333333333333333333333333");
+        assert_eq!(quotient("2", "3"), "0.6666666666666666666666666667");
+        assert_eq!(quotient("-2", "3"), "-0.6666666666666666666666666667");
+        assert_eq!(quotient("0.5", "2"), "0.25");
+        assert_eq!(quotient("1", "0.1"), "10.0");
+    }
+
+    #[test]
+    fn division_rounds_ties_to_even() {
+        // Both have a 5 right past the 28th place, the digit before it decides
+        assert_eq!(quotient("5e-28", "10"), "0.0");
+        assert_eq!(quotient("15e-28", "10"), "0.0000000000000000000000000002");
+        assert_eq!(quotient("25e-28", "10"), "0.0000000000000000000000000002");
+        assert_eq!(quotient("-15e-28", "10"), "-0.0000000000000000000000000002");
+        assert_eq!(quotient("-25e-28", "10"), "-0.0000000000000000000000000002");
+    }
+
+    #[test]
+    fn division_by_zero_is_none() {
+        assert_eq!(dec("1").checked_div(&dec("0.0")), None);
+        assert_eq!(dec("1").floor_div_rem(&dec("0")), None);
+    }
+
+    #[test]
+    fn floor_div_rem_rounds_down() {
+        let (quotient, remainder) = dec("-7.5").floor_div_rem(&dec("2")).unwrap();
+        assert_eq!((quotient.to_string(), remainder.to_string()), ("-4.0".to_string(), "0.5".to_string()));
+
+        let (quotient, remainder) = dec("7.5").floor_div_rem(&dec("-2")).unwrap();
+        assert_eq!((quotient.to_string(), remainder.to_string()), ("-4.0".to_string(), "-0.5".to_string()));
+    }
+
+    #[test]
+    fn big_floor_div_rem_takes_the_sign_of_the_divisor() {
+        let pair = |l: i64, r: i64| floor_div_rem_big(&BigInt::from(l), &BigInt::from(r));
+        assert_eq!(pair(7, 2), (BigInt::from(3), BigInt::from(1)));
+        assert_eq!(pair(-7, 2), (BigInt::from(-4), BigInt::from(1)));
+        assert_eq!(pair(7, -2), (BigInt::from(-4), BigInt::from(-1)));
+        assert_eq!(pair(-7, -2), (BigInt::from(3), BigInt::from(-1)));
+    }
+}
@@ -0,0 +1,87 @@
+/// A location in the source file, counted in characters and starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, length: usize) -> Span {
+        Span {
+            line,
+            column,
+            length,
+        }
+    }
+
+    /// Builds a span going from the start of `self` to the end of `end`.
+    /// Spans covering several lines are only underlined on their first line.
+    pub fn to(self, end: Span) -> Span {
+        if end.line != self.line || end.column + end.length < self.column {
+            return self;
+        }
+
+        Span {
+            line: self.line,
+            column: self.column,
+            length: end.column + end.length - self.column,
+        }
+    }
+}
+
+/// A message ready to be shown to the user, e.g. `syntax error: Expected brace '}'`.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub label: &'static str,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn new(label: &'static str, message: String, span: Span) -> Diagnostic {
+        Diagnostic {
+            label,
+            message,
+            span,
+        }
+    }
+}
+
+/// Renders a diagnostic with the file name, position and the offending line:
+///
+/// ```text
+/// syntax error: Unexpected character: #
+///  --> examples/hello.owo:1:6
+///   |
+/// 1 | meow(# "hi")
+///   |      ^
+/// ```
+pub fn render(diagnostic: &Diagnostic, file_name: &str, source: &str) -> String {
+    let span = diagnostic.span;
+    let line_number = span.line.to_string();
+    let gutter = " ".repeat(line_number.len());
+
+    let mut output = format!(
+        "{}: {}\n{}--> {}:{}:{}\n",
+        diagnostic.label, diagnostic.message, gutter, file_name, span.line, span.column
+    );
+
+    if let Some(line) = source.lines().nth(span.line.saturating_sub(1)) {
+        let chars: Vec<char> = line.chars().collect();
+        let start = span.column.saturating_sub(1).min(chars.len());
+        let length = span.length.max(1).min(chars.len().saturating_sub(start).max(1));
+
+        // Keep tabs in the padding so the carets line up with the source line
+        let padding: String = chars[..start]
+            .iter()
+            .map(|c| if *c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        output.push_str(&format!("{} |\n", gutter));
+        output.push_str(&format!("{} | {}\n", line_number, line));
+        output.push_str(&format!("{} | {}{}", gutter, padding, "^".repeat(length)));
+    }
+
+    output
+}
@@ -1 +1,3 @@
-pub mod owo;
\ No newline at end of file
+pub mod decimal;
+pub mod diagnostics;
+pub mod owo;
@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Printed by the interpreter after every script, with a timing that changes on each run.
+const TRAILER: &str = "------------------------------------\nScript ran in:";
+
+/// What a script printed, without the timing trailer. `owo()` adds " owo" to its line at random,
+/// so that is dropped too.
+fn normalized(output: &str) -> String {
+    let output = output.split(TRAILER).next().unwrap_or_default();
+    output.lines().map(|line| line.strip_suffix(" owo").unwrap_or(line)).map(|line| format!("{}\n", line)).collect()
+}
+
+#[test]
+fn examples_print_what_they_should() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut scripts: Vec<_> = fs::read_dir(&examples)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "owo"))
+        .collect();
+    scripts.sort();
+    assert!(!scripts.is_empty(), "no examples found in {}", examples.display());
+
+    let mut failures = Vec::new();
+    for script in &scripts {
+        let name = script.file_stem().unwrap().to_string_lossy();
+        let expected_path = examples.join("expected").join(format!("{}.out", name));
+        let expected = fs::read_to_string(&expected_path)
+            .unwrap_or_else(|_| panic!("{} has no expected output at {}", name, expected_path.display()));
+
+        let output = Command::new(env!("CARGO_BIN_EXE_owo-pp")).arg(script).output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            failures.push(format!("{} exited with {}:\n{}", name, output.status, String::from_utf8_lossy(&output.stderr)));
+        } else if normalized(&stdout) != normalized(&expected) {
+            failures.push(format!("{} printed:\n{}\nexpected:\n{}", name, normalized(&stdout), expected));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}